@@ -0,0 +1,183 @@
+use crate::solver::guess::State;
+use crate::solver::{DifficultyTier, SudokuSolver, Techniques};
+use crate::sudoku::Sudoku;
+use crate::utils::Rng;
+
+use itertools::Itertools;
+
+/// The symmetry of the givens of a generated puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    None,
+    /// Symmetric under a rotation of 180 degrees around the center cell.
+    Rotational,
+    /// Symmetric under a reflection across the middle column.
+    Mirror,
+}
+
+impl Symmetry {
+    /// The cell that is given or removed together with `cell`. It is `cell` itself if the cell is
+    /// on the axis of the symmetry.
+    pub fn partner(&self, cell: usize) -> usize {
+        match self {
+            Symmetry::None => cell,
+            Symmetry::Rotational => 80 - cell,
+            Symmetry::Mirror => cell / 9 * 9 + 8 - cell % 9,
+        }
+    }
+}
+
+/// Generates a puzzle with a unique solution from `seed`. Values of a random complete grid are
+/// removed in a shuffled order as long as the solution stays unique. With `Symmetry::None` the
+/// result is minimal, see `Sudoku::is_minimal`. Otherwise values are removed together with their
+/// partner, so that removing any remaining pair of values breaks the uniqueness.
+pub fn generate(seed: u64, symmetry: Symmetry) -> Sudoku {
+    dig_holes(seed, symmetry, |_| true)
+}
+
+/// Same as `generate`, but a value is only removed if the puzzle still rates at most `target`, so
+/// the digging stops making the puzzle harder once it reaches the target tier. The result rates
+/// below `target` only if no removal keeps it at the target.
+pub fn generate_rated(seed: u64, symmetry: Symmetry, target: DifficultyTier) -> Sudoku {
+    dig_holes(seed, symmetry, |sudoku| rate_tier(sudoku) <= target)
+}
+
+/// The tier of a sudoku with a unique solution, rated with the default techniques.
+pub fn rate_tier(sudoku: &Sudoku) -> DifficultyTier {
+    let mut solver = SudokuSolver::new(sudoku.clone());
+    solver.initialize_candidates();
+    solver
+        .rate(&Techniques::new())
+        .expect("guessing completes a sudoku with a unique solution")
+        .tier()
+}
+
+// 按打乱的顺序尝试删除数字，删除后解不唯一或 accept 不接受时恢复
+fn dig_holes(seed: u64, symmetry: Symmetry, accept: impl Fn(&Sudoku) -> bool) -> Sudoku {
+    let mut rng = Rng::new(seed);
+    let mut values = random_solution(&mut rng).chars().collect_vec();
+
+    let mut cells = (0..81).collect_vec();
+    rng.shuffle(&mut cells);
+    for cell in cells {
+        let partner = symmetry.partner(cell);
+        if values[cell] == '.' {
+            continue;
+        }
+        let removed = [values[cell], values[partner]];
+        values[cell] = '.';
+        values[partner] = '.';
+        let sudoku = Sudoku::from_values(&values.iter().collect::<String>());
+        if sudoku.solution_count(2) != 1 || !accept(&sudoku) {
+            values[cell] = removed[0];
+            values[partner] = removed[1];
+        }
+    }
+    Sudoku::from_values(&values.iter().collect::<String>())
+}
+
+/// The number of puzzles `Sudoku::random` generates before giving up on an exact match.
+const MAX_RANDOM_ATTEMPTS: usize = 8;
+
+impl Sudoku {
+    /// Generates a puzzle with rotational symmetry that rates at `tier`, e.g. for a "new medium
+    /// puzzle" button. At most `MAX_RANDOM_ATTEMPTS` puzzles are generated, and if none of them
+    /// rates exactly at `tier`, the one with the closest tier is returned.
+    pub fn random(seed: u64, tier: DifficultyTier) -> Sudoku {
+        let mut rng = Rng::new(seed);
+        let mut closest: Option<(usize, Sudoku)> = None;
+        for _ in 0..MAX_RANDOM_ATTEMPTS {
+            let sudoku = generate_rated(rng.next_u64(), Symmetry::Rotational, tier);
+            let distance = rate_tier(&sudoku).distance(tier);
+            if distance == 0 {
+                return sudoku;
+            }
+            if closest.as_ref().is_none_or(|(best, _)| distance < *best) {
+                closest = Some((distance, sudoku));
+            }
+        }
+        closest.unwrap().1
+    }
+}
+
+// 对角线上的三个宫互不影响，分别随机填入打乱的 1-9 后求解即可得到随机的终盘
+fn random_solution(rng: &mut Rng) -> String {
+    let mut values = vec!['.'; 81];
+    for block in 0..3 {
+        let mut digits = ('1'..='9').collect_vec();
+        rng.shuffle(&mut digits);
+        for (i, digit) in digits.into_iter().enumerate() {
+            values[(block * 3 + i / 3) * 9 + block * 3 + i % 3] = digit;
+        }
+    }
+    let mut state = State::from_values(&values.iter().collect::<String>());
+    state
+        .solve()
+        .expect("the diagonal blocks never conflict with each other");
+    state.to_value_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let sudoku = generate(42, Symmetry::None);
+        assert!(sudoku.has_unique_solution());
+        assert!(sudoku.is_minimal());
+        assert_eq!(
+            sudoku.to_value_string(),
+            generate(42, Symmetry::None).to_value_string()
+        );
+        assert_ne!(
+            sudoku.to_value_string(),
+            generate(43, Symmetry::None).to_value_string()
+        );
+
+        for symmetry in [Symmetry::Rotational, Symmetry::Mirror] {
+            let sudoku = generate(42, symmetry);
+            assert!(sudoku.has_unique_solution());
+            let values = sudoku.to_value_string().into_bytes();
+            for cell in 0..81 {
+                let partner = symmetry.partner(cell);
+                assert_eq!(values[cell] == b'.', values[partner] == b'.');
+                if values[cell] != b'.' {
+                    let mut removed = values.clone();
+                    removed[cell] = b'.';
+                    removed[partner] = b'.';
+                    let removed = Sudoku::from_values(std::str::from_utf8(&removed).unwrap());
+                    assert!(!removed.has_unique_solution());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_rated() {
+        for target in [
+            DifficultyTier::Easy,
+            DifficultyTier::Medium,
+            DifficultyTier::Hard,
+        ] {
+            let sudoku = generate_rated(42, Symmetry::Rotational, target);
+            assert!(sudoku.has_unique_solution());
+            let tier = rate_tier(&sudoku);
+            assert!(tier <= target);
+            assert!(tier.distance(target) <= 1);
+        }
+    }
+
+    #[test]
+    fn test_random() {
+        for tier in [DifficultyTier::Medium, DifficultyTier::Hard] {
+            let sudoku = Sudoku::random(7, tier);
+            assert!(sudoku.has_unique_solution());
+            assert!(rate_tier(&sudoku).distance(tier) <= 1);
+            assert_eq!(
+                sudoku.to_value_string(),
+                Sudoku::random(7, tier).to_value_string()
+            );
+        }
+    }
+}
@@ -2,28 +2,99 @@
 #![feature(const_for)]
 #![feature(core_intrinsics)]
 
+pub mod generator;
 pub mod solver;
 mod sudoku;
 pub mod utils;
 
 use solver::Techniques;
 pub use solver::{SolutionRecorder, SudokuSolver, Technique};
-pub use sudoku::Sudoku;
+pub use sudoku::{is_valid_solution, Sudoku, SudokuBuilder, SudokuError};
 
 use wasm_bindgen::prelude::*;
 use std::ffi::CStr;
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
 
 #[cfg(feature = "wee_alloc")]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 #[wasm_bindgen]
-pub fn sudoku_one_step(sudoku: &str) -> Option<SolutionRecorder> {
-    let sudoku = Sudoku::from_values(sudoku);
+pub fn sudoku_one_step(sudoku: &str) -> Result<Option<SolutionRecorder>, JsError> {
+    let sudoku = Sudoku::try_from_values(sudoku)?;
     let solver = SudokuSolver::new(sudoku);
     let techniques = Techniques::new();
-    solver.solve_one_step(&techniques)
+    Ok(solver.solve_one_step(&techniques))
+}
+
+/// A solver that is kept across calls, so that an interactive UI can solve the sudoku step by step
+/// without rebuilding the solver and its caches every time like `sudoku_one_step` does.
+#[wasm_bindgen]
+pub struct InteractiveSolver {
+    solver: SudokuSolver,
+    techniques: Techniques,
+}
+
+#[wasm_bindgen]
+impl InteractiveSolver {
+    /// Fails instead of panicking if `sudoku` is not a valid string of 81 values, see
+    /// `Sudoku::try_from_values`.
+    pub fn new(sudoku: &str) -> Result<InteractiveSolver, JsError> {
+        let sudoku = Sudoku::try_from_values(sudoku)?;
+        Ok(Self {
+            solver: SudokuSolver::new(sudoku),
+            techniques: Techniques::new(),
+        })
+    }
+
+    /// Finds the next step without applying it.
+    pub fn next_step(&self) -> Option<SolutionRecorder> {
+        self.solver.solve_one_step(&self.techniques)
+    }
+
+    /// Applies a step returned by `next_step`.
+    pub fn apply(&mut self, step: &SolutionRecorder) {
+        self.solver.apply_step(step);
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.solver.is_completed()
+    }
+}
+
+/// Counts the solutions of `sudoku`, stopping at `limit`. Returns 0 if `sudoku` is not a valid
+/// string of 81 values.
+///
+/// From JavaScript, pass a limit of 2 to tell whether the puzzle has a unique solution:
+///
+/// ```js
+/// import { sudoku_solution_count } from "sudoku-solver";
+///
+/// if (sudoku_solution_count(puzzle, 2) > 1) {
+///     console.warn("the puzzle has multiple solutions");
+/// }
+/// ```
+#[wasm_bindgen]
+pub fn sudoku_solution_count(sudoku: &str, limit: usize) -> usize {
+    let Ok(sudoku) = Sudoku::try_from_values(sudoku) else {
+        return 0;
+    };
+    solver::guess::State::from_values(&sudoku.to_value_string()).count_solutions(limit)
+}
+
+/// Solves each line of 81 values with the brute force solver. A line is `None` if it is malformed
+/// or has no solution. With the `parallel` feature, the lines are solved in parallel.
+pub fn solve_many(lines: &[&str]) -> Vec<Option<String>> {
+    let solve_line = |line: &&str| Sudoku::try_from_values(line).ok()?.solve_fast();
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        lines.par_iter().map(solve_line).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        lines.iter().map(solve_line).collect()
+    }
 }
 
 // #[no_mangle]
@@ -42,3 +113,177 @@ pub extern "C" fn hudoku_solve(input: *const c_char, limit: usize) -> usize {
     let mut sudoku = solver::guess::State::from_values(&line.to_str().unwrap());
     return sudoku.solve().is_ok() as usize;
 }
+
+/// Solves the sudoku in `input` and writes the 81 digits of the solution with a trailing NUL to `out`.
+/// Returns 0 on success, -1 if the input is invalid, -2 if the sudoku has no solution,
+/// and -3 if `out_len` is less than 82.
+///
+/// # Safety
+///
+/// `input` must be a NUL-terminated string and `out` must be valid for writes of `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hudoku_solve_string(
+    input: *const c_char,
+    out: *mut c_char,
+    out_len: usize,
+) -> c_int {
+    if input.is_null() || out.is_null() {
+        return -1;
+    }
+    let line = CStr::from_ptr(input);
+    let Ok(line) = line.to_str() else {
+        return -1;
+    };
+    let Ok(sudoku) = Sudoku::try_from_values(line) else {
+        return -1;
+    };
+    let mut state = solver::guess::State::from_values(&sudoku.to_value_string());
+    if state.solve().is_err() {
+        return -2;
+    }
+    let solution = state.to_value_string();
+    if out_len < solution.len() + 1 {
+        return -3;
+    }
+    std::ptr::copy_nonoverlapping(solution.as_ptr() as *const c_char, out, solution.len());
+    *out.add(solution.len()) = 0;
+    0
+}
+
+/// Counts the solutions of the sudoku in `input`, stopping at `limit`.
+/// Returns `usize::MAX` if the input is null or invalid.
+///
+/// # Safety
+///
+/// `input` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hudoku_count_solutions(input: *const c_char, limit: usize) -> usize {
+    if input.is_null() {
+        return usize::MAX;
+    }
+    let line = CStr::from_ptr(input);
+    let Ok(line) = line.to_str() else {
+        return usize::MAX;
+    };
+    let Ok(sudoku) = Sudoku::try_from_values(line) else {
+        return usize::MAX;
+    };
+    let mut state = solver::guess::State::from_values(&sudoku.to_value_string());
+    state.count_solutions(limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_interactive_solver() {
+        let Ok(mut interactive) = InteractiveSolver::new(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        ) else {
+            panic!("the sudoku is valid");
+        };
+        let mut count = 0;
+        while let Some(step) = interactive.next_step() {
+            // The warm caches must give the same step as a solver built from scratch
+            let fresh = SudokuSolver::new(interactive.solver.take_sudoku());
+            let expected = fresh.solve_one_step(&Techniques::new()).unwrap();
+            assert_eq!(step.steps, expected.steps);
+            interactive.apply(&step);
+            count += 1;
+        }
+        assert!(count > 1);
+        assert!(interactive.is_completed());
+        assert_eq!(
+            interactive.solver.sudoku().to_value_string(),
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+        );
+    }
+
+    #[test]
+    fn test_sudoku_solution_count() {
+        let puzzle =
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        assert_eq!(sudoku_solution_count(puzzle, 2), 1);
+        assert_eq!(sudoku_solution_count(&".".repeat(81), 2), 2);
+        assert_eq!(sudoku_solution_count(&".".repeat(81), 0), 0);
+        assert_eq!(
+            sudoku_solution_count(&format!("55{}", ".".repeat(79)), 2),
+            0
+        );
+        assert_eq!(sudoku_solution_count("53..7", 2), 0);
+        assert_eq!(sudoku_solution_count(&format!("{}5", puzzle), 2), 0);
+    }
+
+    #[test]
+    fn test_solve_many() {
+        let puzzle =
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        let hard =
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..";
+        let conflicting = format!("55{}", &puzzle[2..]);
+        assert_eq!(
+            solve_many(&[puzzle, "53..7", hard, &conflicting, "x".repeat(81).as_str()]),
+            vec![
+                Some(
+                    "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+                        .to_string()
+                ),
+                None,
+                Some(
+                    "812753649943682175675491283154237896369845721287169534521974368438526917796318452"
+                        .to_string()
+                ),
+                None,
+                None,
+            ]
+        );
+        assert!(solve_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_hudoku_solve_string() {
+        let input = CString::new(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        )
+        .unwrap();
+        let mut out = [0 as c_char; 82];
+        let solve = |input: &CString, out: &mut [c_char], out_len: usize| unsafe {
+            hudoku_solve_string(input.as_ptr(), out.as_mut_ptr(), out_len)
+        };
+        assert_eq!(solve(&input, &mut out, 82), 0);
+        let solution = unsafe { CStr::from_ptr(out.as_ptr()) };
+        assert_eq!(
+            solution.to_str().unwrap(),
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+        );
+
+        assert_eq!(solve(&input, &mut out, 81), -3);
+        let invalid = CString::new("53..7").unwrap();
+        assert_eq!(solve(&invalid, &mut out, 82), -1);
+        let unsolvable = CString::new(format!("55{}", ".".repeat(79))).unwrap();
+        assert_eq!(solve(&unsolvable, &mut out, 82), -2);
+    }
+
+    #[test]
+    fn test_hudoku_count_solutions() {
+        let count = |input: &str, limit: usize| {
+            let input = CString::new(input).unwrap();
+            unsafe { hudoku_count_solutions(input.as_ptr(), limit) }
+        };
+        assert_eq!(
+            count(
+                "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+                2
+            ),
+            1
+        );
+        assert_eq!(count(&".".repeat(81), 2), 2);
+        assert_eq!(count("53..7", 2), usize::MAX);
+        assert_eq!(
+            unsafe { hudoku_count_solutions(std::ptr::null(), 2) },
+            usize::MAX
+        );
+    }
+}
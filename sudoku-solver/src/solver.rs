@@ -1,37 +1,187 @@
+mod als;
+mod bug;
 mod chain;
+mod chute;
+mod death_blossom;
+mod exocet;
 mod fish;
 mod intersection;
+mod killer;
+mod medusa;
+mod remote_pairs;
 mod single;
 mod single_digit_patterns;
 mod subset;
+mod template;
+mod unique_rectangle;
 mod wing;
 pub mod guess;
 
+pub use chain::{
+    solve_forced_chain_with_max_length, solve_xy_chain_with_max_length,
+    DEFAULT_MAX_XY_CHAIN_LENGTH,
+};
+pub use killer::Cage;
+
 use crate::sudoku::{CellIndex, CellValue, Sudoku};
 use crate::utils::{CellSet, NamedCellSet, ValueSet};
 
-use std::cell::OnceCell;
-use std::collections::HashSet;
+use std::cell::{OnceCell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
 use arrayvec::ArrayVec;
 use itertools::Itertools;
+use rustc_hash::FxHashSet;
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
-pub struct SudokuSolver {
-    sudoku: Sudoku,
+/// Cells of the 27 houses, in the order of `SudokuSolver::all_constraints`: blocks, rows, then
+/// columns.
+const HOUSE_BITSETS: [u128; 27] = house_bitsets();
+/// Cells sharing a house with each cell, excluding the cell itself.
+pub(crate) const PEER_BITSETS: [u128; 81] = peer_bitsets();
+/// The row, column and block of each cell.
+const CELL_POSITIONS: [(u8, u8, u8); 81] = cell_positions();
+
+const fn house_bitsets() -> [u128; 27] {
+    let mut houses = [0; 27];
+    let mut cell = 0;
+    while cell < 81 {
+        let (row, col) = (cell / 9, cell % 9);
+        houses[row / 3 * 3 + col / 3] |= 1 << cell;
+        houses[9 + row] |= 1 << cell;
+        houses[18 + col] |= 1 << cell;
+        cell += 1;
+    }
+    houses
+}
+
+const fn peer_bitsets() -> [u128; 81] {
+    let houses = house_bitsets();
+    let mut peers = [0; 81];
+    let mut cell = 0;
+    while cell < 81 {
+        let (row, col) = (cell / 9, cell % 9);
+        peers[cell] =
+            (houses[row / 3 * 3 + col / 3] | houses[9 + row] | houses[18 + col]) & !(1 << cell);
+        cell += 1;
+    }
+    peers
+}
+
+const fn cell_positions() -> [(u8, u8, u8); 81] {
+    let mut positions = [(0, 0, 0); 81];
+    let mut cell = 0;
+    while cell < 81 {
+        let (row, col) = (cell / 9, cell % 9);
+        positions[cell] = (row as u8, col as u8, (row / 3 * 3 + col / 3) as u8);
+        cell += 1;
+    }
+    positions
+}
 
+/// The houses of the standard grid, which are the same for all solvers.
+struct Geometry {
     all_constraints: Vec<NamedCellSet>,
     constraints_of_cell: Vec<Vec<NamedCellSet>>,
     house_union_of_cell: Vec<CellSet>,
 
-    filled_cells: CellSet,
-    unfilled_cells: CellSet,
-
     cells_in_rows: Vec<NamedCellSet>,
     cells_in_columns: Vec<NamedCellSet>,
     cells_in_blocks: Vec<NamedCellSet>,
+    cells_in_bands: Vec<NamedCellSet>,
+    cells_in_stacks: Vec<NamedCellSet>,
+}
+
+thread_local! {
+    // The cell sets cache their cells in a `OnceCell` and cannot be shared between threads, so each
+    // thread builds the geometry once and shares it with all the solvers created on it.
+    static GEOMETRY: &'static Geometry = Box::leak(Box::new(Geometry::new()));
+}
+
+impl Geometry {
+    fn new() -> Self {
+        let mut all_constraints = vec![];
+        let mut constraints_of_cell = (0..81).map(|_| vec![]).collect::<Vec<_>>();
+        let mut cells_in_rows = vec![];
+        let mut cells_in_columns = vec![];
+        let mut cells_in_blocks = vec![];
+
+        for (idx, &bitset) in HOUSE_BITSETS.iter().enumerate() {
+            let name = match idx {
+                0..9 => format!("b{}", idx + 1),
+                9..18 => format!("r{}", idx - 9 + 1),
+                _ => format!("c{}", idx - 18 + 1),
+            };
+            let mut house = NamedCellSet::new(name, idx);
+            *house = CellSet::from_bitset_const(bitset);
+            house.values();
+            all_constraints.push(house.clone());
+            match idx {
+                0..9 => cells_in_blocks.push(house),
+                9..18 => cells_in_rows.push(house),
+                _ => cells_in_columns.push(house),
+            }
+        }
+
+        // Chutes are not houses, so their indices start after the 27 houses.
+        let mut cells_in_bands = vec![];
+        let mut cells_in_stacks = vec![];
+        for chute in 0..3 {
+            let mut band_set = NamedCellSet::new(format!("band{}", chute + 1), 27 + chute);
+            let mut stack_set = NamedCellSet::new(format!("stack{}", chute + 1), 30 + chute);
+            for line in chute * 3..chute * 3 + 3 {
+                *band_set |= &cells_in_rows[line];
+                *stack_set |= &cells_in_columns[line];
+            }
+            band_set.values();
+            stack_set.values();
+            cells_in_bands.push(band_set);
+            cells_in_stacks.push(stack_set);
+        }
+
+        for row in 0..9 {
+            for col in 0..9 {
+                let pos = row * 9 + col;
+                let block_idx = row / 3 * 3 + col / 3;
+                constraints_of_cell[pos].push(cells_in_rows[row].clone());
+                constraints_of_cell[pos].push(cells_in_columns[col].clone());
+                constraints_of_cell[pos].push(cells_in_blocks[block_idx].clone());
+            }
+        }
+        let house_union_of_cell = PEER_BITSETS
+            .iter()
+            .map(|&bitset| {
+                let peers = CellSet::from_bitset_const(bitset);
+                peers.values();
+                peers
+            })
+            .collect();
+
+        Self {
+            all_constraints,
+            constraints_of_cell,
+            house_union_of_cell,
+            cells_in_rows,
+            cells_in_columns,
+            cells_in_blocks,
+            cells_in_bands,
+            cells_in_stacks,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct SudokuSolver {
+    sudoku: Sudoku,
+
+    filled_cells: CellSet,
+    /// The givens of the sudoku, see `Sudoku::is_given`. If the sudoku does not tell its givens,
+    /// e.g. when read from candidates, the cells that were filled when the solver was created.
+    /// Values placed by `apply_step` are never added to it.
+    givens: CellSet,
+    unfilled_cells: CellSet,
+
     candidate_cells_in_rows: OnceCell<Vec<Vec<NamedCellSet>>>,
     candidate_cells_in_columns: OnceCell<Vec<Vec<NamedCellSet>>>,
     candidate_cells_in_blocks: OnceCell<Vec<Vec<NamedCellSet>>>,
@@ -62,6 +212,15 @@ pub struct SudokuSolver {
     >,
 
     possible_positions_for_house_and_value: Vec<OnceCell<NamedCellSet>>,
+
+    /// If true, `solve_one_step` skips the deductions it has already returned until a step is
+    /// applied or `reset_served_steps` is called. A deduction is keyed by its technique and reason,
+    /// so it is skipped as a whole rather than one elimination at a time.
+    skip_served_steps: bool,
+    served_steps: RefCell<FxHashSet<(Technique, String)>>,
+
+    /// The cages of a killer sudoku, only used by `Technique::KillerCombinations`.
+    cages: Vec<Cage>,
 }
 
 macro_rules! return_if_some {
@@ -87,6 +246,23 @@ impl SudokuSolver {
         &self.sudoku
     }
 
+    /// Sets the cages of a killer sudoku, see `Technique::KillerCombinations`.
+    pub fn with_cages(mut self, cages: Vec<Cage>) -> Self {
+        self.cages = cages;
+        self
+    }
+
+    pub(crate) fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// A new solver for `sudoku` that keeps the cages and the givens of this solver.
+    fn derive(&self, sudoku: Sudoku) -> SudokuSolver {
+        let mut solver = SudokuSolver::new(sudoku).with_cages(self.cages.clone());
+        solver.givens = self.givens.clone();
+        solver
+    }
+
     pub(crate) fn cells(&self) -> impl Iterator<Item = CellIndex> {
         (0..81).map(|x| x as CellIndex)
     }
@@ -96,11 +272,8 @@ impl SudokuSolver {
     }
 
     pub(crate) fn cell_position(&self, cell: CellIndex) -> (usize, usize, usize) {
-        (
-            cell as usize / 9,
-            cell as usize % 9,
-            (cell / 27 * 3 + cell % 9 / 3) as usize,
-        )
+        let (row, col, block) = CELL_POSITIONS[cell as usize];
+        (row as usize, col as usize, block as usize)
     }
 
     pub(crate) fn cell_value(&self, idx: CellIndex) -> Option<CellValue> {
@@ -123,22 +296,30 @@ impl SudokuSolver {
         &self.filled_cells
     }
 
+    /// The givens of the puzzle, as opposed to the cells filled by applying steps.
+    pub fn givens(&self) -> &CellSet {
+        &self.givens
+    }
+
     pub(crate) fn can_fill(&self, idx: CellIndex, value: CellValue) -> bool {
         self.sudoku.can_fill(idx, value)
     }
 
+    fn geometry(&self) -> &'static Geometry {
+        GEOMETRY.with(|geometry| *geometry)
+    }
+
     pub(crate) fn all_constraints(&self) -> &[NamedCellSet] {
-        &self.all_constraints
+        &self.geometry().all_constraints
     }
 
     pub(crate) fn constraints_of_cell(&self, idx: CellIndex) -> &[NamedCellSet] {
-        &self.constraints_of_cell[idx as usize]
+        &self.geometry().constraints_of_cell[idx as usize]
     }
 
-    pub(crate) fn house_union_of_cell<'a, 'b>(&'a self, idx: CellIndex) -> &'b CellSet {
-        // To prevent the return value holding the reference to self, we use unsafe code here.
-        // SAFETY: house_union_of_cell is initialized and never changed
-        unsafe { &*(&self.house_union_of_cell[idx as usize] as *const _) }
+    // The return value is shared by all solvers, so it does not hold the reference to self.
+    pub(crate) fn house_union_of_cell(&self, idx: CellIndex) -> &'static CellSet {
+        &self.geometry().house_union_of_cell[idx as usize]
     }
 
     pub(crate) fn cell_of_intersection(
@@ -161,22 +342,34 @@ impl SudokuSolver {
     }
 
     pub(crate) fn cells_in_rows(&self) -> &[NamedCellSet] {
-        &self.cells_in_rows
+        &self.geometry().cells_in_rows
     }
 
     pub(crate) fn cells_in_columns(&self) -> &[NamedCellSet] {
-        &self.cells_in_columns
+        &self.geometry().cells_in_columns
     }
 
     pub(crate) fn cells_in_blocks(&self) -> &[NamedCellSet] {
-        &self.cells_in_blocks
+        &self.geometry().cells_in_blocks
+    }
+
+    /// The three horizontal chutes, each composed of three blocks (and three rows).
+    /// The layout is the same as the horizontal bands in `guess::State`.
+    pub(crate) fn cells_in_bands(&self) -> &[NamedCellSet] {
+        &self.geometry().cells_in_bands
+    }
+
+    /// The three vertical chutes, each composed of three blocks (and three columns).
+    /// The layout is the same as the vertical bands in `guess::State`.
+    pub(crate) fn cells_in_stacks(&self) -> &[NamedCellSet] {
+        &self.geometry().cells_in_stacks
     }
 
     pub(crate) fn candidate_cells_in_rows(&self, value: CellValue) -> &[NamedCellSet] {
         &self.candidate_cells_in_rows.get_or_init(|| {
             (1..=9)
                 .map(|value| {
-                    self.cells_in_rows
+                    self.cells_in_rows()
                         .iter()
                         .map(|row| {
                             NamedCellSet::from_cellset(row, self.possible_cells(value) & row)
@@ -191,7 +384,7 @@ impl SudokuSolver {
         &self.candidate_cells_in_columns.get_or_init(|| {
             (1..=9)
                 .map(|value| {
-                    self.cells_in_columns
+                    self.cells_in_columns()
                         .iter()
                         .map(|col| {
                             NamedCellSet::from_cellset(col, self.possible_cells(value) & col)
@@ -206,7 +399,7 @@ impl SudokuSolver {
         &self.candidate_cells_in_blocks.get_or_init(|| {
             (1..=9)
                 .map(|value| {
-                    self.cells_in_blocks
+                    self.cells_in_blocks()
                         .iter()
                         .map(|block| {
                             NamedCellSet::from_cellset(block, self.possible_cells(value) & block)
@@ -305,17 +498,394 @@ impl SudokuSolver {
     pub(crate) fn get_cellset_string(&self, cellset: &CellSet) -> String {
         cellset.iter().map(|idx| self.get_cell_name(idx)).join(",")
     }
+
+    /// Candidates still present in unfilled cells that differ from the value of the cell in
+    /// `solution`, i.e. candidates that can still be eliminated.
+    pub fn find_wrong_candidates(&self, solution: &Sudoku) -> Vec<(CellIndex, CellValue)> {
+        let mut wrong_candidates = vec![];
+        for cell in self.unfilled_cells().iter() {
+            if let Some(expected) = solution.get_cell_value(cell) {
+                for value in self.candidates(cell).iter() {
+                    if value != expected {
+                        wrong_candidates.push((cell, value));
+                    }
+                }
+            }
+        }
+        wrong_candidates
+    }
+
+    /// Values of `solution` that are no longer candidates of the corresponding unfilled cells,
+    /// i.e. candidates that have been eliminated by mistake.
+    pub fn find_missing_candidates(&self, solution: &Sudoku) -> Vec<(CellIndex, CellValue)> {
+        let mut missing_candidates = vec![];
+        for cell in self.unfilled_cells().iter() {
+            if let Some(expected) = solution.get_cell_value(cell) {
+                if !self.candidates(cell).has(expected) {
+                    missing_candidates.push((cell, expected));
+                }
+            }
+        }
+        missing_candidates
+    }
+
+    /// The candidates of `cell` in ascending order. A filled cell has no candidates.
+    ///
+    /// ```
+    /// use sudoku_solver::{Sudoku, SudokuSolver};
+    ///
+    /// let sudoku = Sudoku::from_values(
+    ///     "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+    /// );
+    /// let mut solver = SudokuSolver::new(sudoku);
+    /// solver.initialize_candidates();
+    /// let candidates: Vec<_> = solver.cell_candidates(2).collect();
+    /// assert_eq!(candidates, vec![1, 2, 4]);
+    /// assert_eq!(solver.cell_candidates(0).count(), 0);
+    /// ```
+    pub fn cell_candidates(&self, cell: CellIndex) -> impl Iterator<Item = CellValue> + '_ {
+        let candidates = match self.cell_value(cell) {
+            Some(_) => &[][..],
+            None => self.candidates(cell).values(),
+        };
+        candidates.iter().copied()
+    }
+
+    /// All the candidates of the unfilled cells, ordered by cell and then by value.
+    pub fn all_candidates(&self) -> impl Iterator<Item = (CellIndex, CellValue)> + '_ {
+        self.unfilled_cells()
+            .iter()
+            .flat_map(|cell| self.cell_candidates(cell).map(move |value| (cell, value)))
+    }
+
+    /// Whether the filled values can be completed to a solution, checked with the brute force
+    /// solver. The candidates are not taken into account.
+    pub fn is_solvable(&self) -> bool {
+        self.sudoku.solution_count(1) > 0
+    }
+
+    /// Checks that no value is filled twice in a house and that every unfilled cell has a candidate.
+    pub fn validate(&self) -> Result<(), Vec<Conflict>> {
+        let mut conflicts = vec![];
+        for house in self.all_constraints().iter() {
+            for value in 1..=9 {
+                let cells = house
+                    .iter()
+                    .filter(|&cell| self.cell_value(cell) == Some(value))
+                    .collect_vec();
+                if cells.len() > 1 {
+                    conflicts.push(Conflict {
+                        kind: ConflictKind::DuplicateValue {
+                            house_name: house.name().to_string(),
+                            value,
+                        },
+                        cells,
+                    });
+                }
+            }
+        }
+        for cell in self.unfilled_cells().iter() {
+            if self.candidates(cell).is_empty() {
+                conflicts.push(Conflict {
+                    kind: ConflictKind::NoCandidates,
+                    cells: vec![cell],
+                });
+            }
+        }
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Candidates that `apply_step` would remove for `step`, without modifying the sudoku. For a
+    /// value set, these are the other candidates of the cell and the value in all its peers.
+    pub fn candidates_removed_by(&self, step: &Step) -> Vec<(CellIndex, CellValue)> {
+        let cell = step.cell_index;
+        match step.kind {
+            StepKind::ValueSet => {
+                let mut removed = self
+                    .candidates(cell)
+                    .iter()
+                    .filter(|&value| value != step.value)
+                    .map(|value| (cell, value))
+                    .collect_vec();
+                for peer in self.house_union_of_cell(cell).iter() {
+                    if self.can_fill(peer, step.value) {
+                        removed.push((peer, step.value));
+                    }
+                }
+                removed
+            }
+            StepKind::CandidateEliminated => {
+                if self.can_fill(cell, step.value) {
+                    vec![(cell, step.value)]
+                } else {
+                    vec![]
+                }
+            }
+        }
+    }
+
+    /// Same as `solve_one_step`, but runs the techniques concurrently and returns the result of the
+    /// first technique in `techniques` that finds a step. Each technique works on its own copy of
+    /// the solver since the lazily computed caches are not thread safe.
+    #[cfg(feature = "parallel")]
+    pub fn solve_one_step_parallel(&self, techniques: &Techniques) -> Option<SolutionRecorder> {
+        use rayon::prelude::*;
+
+        let ignored_steps = if self.skip_served_steps {
+            self.served_steps.borrow().clone()
+        } else {
+            FxHashSet::default()
+        };
+        let solvers = techniques
+            .solver_fns
+            .iter()
+            .map(|&technique| (technique, self.derive(self.take_sudoku())))
+            .collect_vec();
+        let solution = solvers
+            .into_par_iter()
+            .map(|(technique, solver)| {
+                let mut solution = SolutionRecorder {
+                    ignored_steps: ignored_steps.clone(),
+                    full_house_enabled: techniques.contains(Technique::FullHouse),
+                    ..SolutionRecorder::new()
+                };
+                technique(&solver, &mut solution);
+                solution
+            })
+            .find_first(|solution| !solution.is_empty())?;
+        self.record_served_steps(&solution);
+        Some(solution)
+    }
+
+    /// Counts the cells by their number of candidates: index `k` is the number of cells with
+    /// exactly `k` candidates, and index 0 is the number of filled cells.
+    pub fn candidate_histogram(&self) -> [usize; 10] {
+        let mut histogram = [0; 10];
+        histogram[0] = self.filled_cells().size();
+        for cell in self.unfilled_cells().iter() {
+            histogram[self.candidates(cell).size()] += 1;
+        }
+        histogram
+    }
+
+    /// Exports the implication graph of the candidates used by `ForcedChain` in Graphviz DOT
+    /// format, with nodes labeled as `r1c1=1` or `r1c1<>1`.
+    pub fn as_dot(&self) -> String {
+        let (graph, _, _) = chain::build_implication_graph(self);
+        graph.to_dot(self, false)
+    }
+
+    /// Lists all bivalue cells and all bilocation values in houses of the current grid.
+    pub fn strong_links(&self) -> Vec<StrongLink> {
+        let mut links = vec![];
+        for cell in self.unfilled_cells().iter() {
+            if let [value1, value2] = self.candidates(cell).values()[..] {
+                links.push(StrongLink::Bivalue {
+                    cell,
+                    values: (value1, value2),
+                });
+            }
+        }
+        for house in self.all_constraints().iter() {
+            for value in 1..=9 {
+                let cells = self.get_possible_cells_for_house_and_value(house, value);
+                if let [cell1, cell2] = cells.values()[..] {
+                    links.push(StrongLink::Bilocation {
+                        house: house.idx(),
+                        value,
+                        cells: (cell1, cell2),
+                    });
+                }
+            }
+        }
+        links
+    }
+
+    /// Clears the values placed by the player that differ from `solution`. As with
+    /// `Sudoku::set_cell`, the candidates of the cleared cells are recomputed from their peers, and
+    /// the wrong values are restored as candidates of the peers where no other peer holds them.
+    pub fn revert_to_consistent(&mut self, solution: &Sudoku) {
+        let mut sudoku = self.take_sudoku();
+        let wrong_cells = sudoku
+            .diff(solution)
+            .into_iter()
+            .filter(|&cell| !self.givens.has(cell))
+            .collect_vec();
+        if wrong_cells.is_empty() {
+            return;
+        }
+
+        for &cell in wrong_cells.iter() {
+            sudoku.set_cell(cell, None, false).unwrap();
+        }
+
+        let skip_served_steps = self.skip_served_steps;
+        *self = self.derive(sudoku);
+        self.skip_served_steps = skip_served_steps;
+    }
+
+    /// Finds all the steps of all `techniques`, ordered from the easiest to the hardest by
+    /// `Technique::weight`, for hints of increasing difficulty. A deduction found by several
+    /// techniques is only kept for the easiest one.
+    pub fn ranked_hints(&self, techniques: &Techniques) -> Vec<Step> {
+        let mut hints = vec![];
+        for technique in techniques.solver_fns.iter() {
+            let mut solution = SolutionRecorder {
+                fast_mode: false,
+                full_house_enabled: techniques.contains(Technique::FullHouse),
+                ..SolutionRecorder::new()
+            };
+            technique(self, &mut solution);
+            hints.extend(solution.steps);
+        }
+        hints.sort_by_key(|step| step.technique.weight());
+        let mut seen = FxHashSet::default();
+        hints.retain(|step| seen.insert((step.kind, step.cell_index, step.value)));
+        hints
+    }
+
+    /// Whether `techniques` can complete the sudoku without guessing.
+    pub fn is_solved_by_logic(&self, techniques: &Techniques) -> bool {
+        let mut solver = self.derive(self.take_sudoku());
+        solver.initialize_candidates();
+        solver.solve_to_end(techniques);
+        solver.is_completed()
+    }
+
+    /// Applies steps found by `techniques` until none can be found or the sudoku is completed.
+    /// Returns the applied steps.
+    pub fn solve_to_end(&mut self, techniques: &Techniques) -> Vec<SolutionRecorder> {
+        let mut steps = vec![];
+        while !self.is_completed() {
+            let Some(step) = self.solve_one_step(techniques) else {
+                break;
+            };
+            self.apply_step(&step);
+            steps.push(step);
+        }
+        steps
+    }
+
+    /// Same as `solve_to_end`, but stops as soon as the sudoku becomes invalid, e.g. because of a
+    /// wrong candidate elimination made before, and returns the invalid positions instead.
+    pub fn solve(
+        &mut self,
+        techniques: &Techniques,
+    ) -> Result<Vec<SolutionRecorder>, Vec<CellIndex>> {
+        let mut steps = vec![];
+        loop {
+            let invalid_positions = self.get_invalid_positions();
+            if !invalid_positions.is_empty() {
+                return Err(invalid_positions);
+            }
+            if self.is_completed() {
+                break;
+            }
+            let Some(step) = self.solve_one_step(techniques) else {
+                break;
+            };
+            self.apply_step(&step);
+            steps.push(step);
+        }
+        Ok(steps)
+    }
+
+    /// The next step found by `techniques`, as a `Hint` describing a single pattern.
+    pub fn hint(&self, techniques: &Techniques) -> Option<Hint> {
+        let solution = self.solve_one_step(techniques)?;
+        let first = solution.steps.first()?;
+        Some(Hint {
+            technique: first.technique.clone(),
+            technique_name: first.technique_name(),
+            kind: first.kind,
+            reason: first.reason.clone(),
+            candidates: solution
+                .steps
+                .iter()
+                .filter(|step| step.kind == first.kind && step.reason == first.reason)
+                .map(|step| (step.cell_index, step.value))
+                .collect(),
+        })
+    }
+
+    /// Solves the sudoku with `techniques` and rates it by the techniques used, see
+    /// `Technique::weight`. Returns `None` if the sudoku is already completed, or if `techniques`
+    /// cannot complete it.
+    pub fn rate(&mut self, techniques: &Techniques) -> Option<DifficultyRating> {
+        let steps = self.solve(techniques).ok()?;
+        if !self.is_completed() {
+            return None;
+        }
+        let used_techniques = steps
+            .iter()
+            .filter_map(|step| step.steps.first())
+            .map(|step| step.technique.clone())
+            .collect_vec();
+        Some(DifficultyRating {
+            hardest_technique: used_techniques
+                .iter()
+                .max_by_key(|technique| technique.weight())?
+                .clone(),
+            score: used_techniques.iter().map(Technique::weight).sum(),
+            needs_guessing: used_techniques.contains(&Technique::Guess),
+        })
+    }
+
+    /// Finds the guesses needed to complete the sudoku when `techniques` stall. Each guess is made
+    /// at the cell with the fewest candidates, and its value is the one confirmed by the brute
+    /// force solver. Returns an empty list if `techniques` are enough to complete the sudoku.
+    pub fn required_guesses(&self, techniques: &Techniques) -> Vec<(CellIndex, CellValue)> {
+        let mut solver = self.derive(self.take_sudoku());
+        let mut guesses = vec![];
+        loop {
+            solver.solve_to_end(techniques);
+            if solver.is_completed() {
+                break;
+            }
+
+            let mut brute_force = SolutionRecorder {
+                fast_mode: false,
+                ..SolutionRecorder::new()
+            };
+            guess::solve_guess(&solver, &mut brute_force);
+            let Some(cell) = solver
+                .unfilled_cells()
+                .iter()
+                .min_by_key(|&cell| solver.candidates(cell).size())
+            else {
+                break;
+            };
+            let Some(value) = brute_force
+                .steps
+                .iter()
+                .find(|step| step.cell_index == cell)
+                .map(|step| step.value)
+            else {
+                // The sudoku has no solution
+                break;
+            };
+
+            let mut step = SolutionRecorder::new();
+            step.add_value_set(
+                Technique::Guess,
+                format!("guess {} in {}", value, solver.get_cell_name(cell)),
+                cell,
+                value,
+            );
+            solver.apply_step(&step);
+            guesses.push((cell, value));
+        }
+        guesses
+    }
 }
 
 #[wasm_bindgen]
 impl SudokuSolver {
     pub fn new(sudoku: Sudoku) -> Self {
-        let mut all_constraints = vec![];
-        let mut constraints_of_cell = (0..81).map(|_| vec![]).collect::<Vec<_>>();
-        let mut house_union_of_cell = (0..81).map(|_| CellSet::new()).collect::<Vec<_>>();
-        let mut cells_in_rows = vec![];
-        let mut cells_in_columns = vec![];
-        let mut cells_in_blocks = vec![];
         let possible_positions_for_house_and_value = vec![OnceCell::new(); 27 * 9];
 
         let filled_cells = CellSet::from_iter(
@@ -329,71 +899,18 @@ impl SudokuSolver {
                 .collect_vec(),
         );
 
-        for block_x in (0..9).step_by(3) {
-            for block_y in (0..9).step_by(3) {
-                let mut block_set = NamedCellSet::new(
-                    format!("b{}", block_x + block_y / 3 + 1),
-                    block_x + block_y / 3,
-                );
-                for x in 0..3 {
-                    for y in 0..3 {
-                        let pos = sudoku.get_cell_position(block_x + x, block_y + y);
-                        block_set.add(pos);
-                    }
-                }
-                all_constraints.push(block_set.clone());
-                cells_in_blocks.push(block_set);
-            }
-        }
-
-        for row in 0..9 {
-            let mut row_set = NamedCellSet::new(format!("r{}", row + 1), 9 + row);
-            for col in 0..9 {
-                let pos = sudoku.get_cell_position(row, col);
-                row_set.add(pos);
-            }
-            all_constraints.push(row_set.clone());
-            cells_in_rows.push(row_set);
-        }
-
-        for col in 0..9 {
-            let mut col_set = NamedCellSet::new(format!("c{}", col + 1), 18 + col);
-            for row in 0..9 {
-                let pos = sudoku.get_cell_position(row, col);
-                col_set.add(pos);
-            }
-            all_constraints.push(col_set.clone());
-            cells_in_columns.push(col_set);
-        }
-
-        for row in 0..9 {
-            for col in 0..9 {
-                let pos = sudoku.get_cell_position(row, col) as usize;
-                let block_x = row / 3;
-                let block_y = col / 3;
-                let block_idx = block_x * 3 + block_y;
-                constraints_of_cell[pos].push(cells_in_rows[row].clone());
-                constraints_of_cell[pos].push(cells_in_columns[col].clone());
-                constraints_of_cell[pos].push(cells_in_blocks[block_idx].clone());
-                house_union_of_cell[pos] =
-                    &(&cells_in_rows[row] | &cells_in_columns[col]) | &cells_in_blocks[block_idx];
-                house_union_of_cell[pos].remove(pos as CellIndex);
-            }
-        }
+        let givens = if sudoku.givens().is_empty() {
+            filled_cells.clone()
+        } else {
+            sudoku.givens().clone()
+        };
 
         SudokuSolver {
             sudoku,
-
-            all_constraints,
-            constraints_of_cell,
-            house_union_of_cell,
-
+            givens,
             filled_cells,
             unfilled_cells,
 
-            cells_in_rows,
-            cells_in_columns,
-            cells_in_blocks,
             candidate_cells_in_rows: OnceCell::new(),
             candidate_cells_in_columns: OnceCell::new(),
             candidate_cells_in_blocks: OnceCell::new(),
@@ -402,6 +919,11 @@ impl SudokuSolver {
             cols_with_only_two_possible_places: vec![OnceCell::new(); 9],
 
             possible_positions_for_house_and_value,
+
+            skip_served_steps: false,
+            served_steps: RefCell::new(FxHashSet::default()),
+
+            cages: vec![],
         }
     }
 
@@ -409,41 +931,48 @@ impl SudokuSolver {
         self.sudoku.clone()
     }
 
+    /// Exports the values and candidates, including manual candidate edits, see
+    /// `Sudoku::to_state_string`.
+    pub fn export_state(&self) -> String {
+        self.sudoku.to_state_string()
+    }
+
+    /// Restores the state produced by `export_state`.
+    pub fn import_state(&mut self, state: &str) -> Result<(), String> {
+        let sudoku = Sudoku::from_state_string(state).map_err(|err| err.to_string())?;
+        let skip_served_steps = self.skip_served_steps;
+        let cages = std::mem::take(&mut self.cages);
+        *self = SudokuSolver::new(sudoku).with_cages(cages);
+        self.skip_served_steps = skip_served_steps;
+        Ok(())
+    }
+
+    /// The cells of the conflicts found by `validate`, followed by the cells whose candidates are
+    /// inconsistent with the cached possible cells of the values.
     pub fn get_invalid_positions(&self) -> Vec<CellIndex> {
-        let mut invalid_positions = vec![];
-        for house in self.all_constraints.iter() {
-            for (i, cell1) in house.iter().enumerate() {
-                if self.cell_value(cell1).is_none() {
-                    if self.candidates(cell1).size() == 0 || self.candidates(cell1).size() > 9 {
-                        invalid_positions.push(cell1);
-                        debug_assert!(false, "Invalid candidates for cell {}", cell1);
-                    }
-                    for value in self.candidates(cell1).iter() {
-                        if !self.possible_cells(value).has(cell1) {
-                            invalid_positions.push(cell1);
-                            debug_assert!(false, "Invalid candidates for cell {}", cell1);
+        let mut invalid_positions = match self.validate() {
+            Ok(()) => vec![],
+            Err(conflicts) => conflicts
+                .into_iter()
+                .flat_map(|conflict| conflict.cells)
+                .collect(),
+        };
+        for house in self.all_constraints().iter() {
+            for cell in house.iter() {
+                if self.cell_value(cell).is_none() {
+                    for value in self.candidates(cell).iter() {
+                        if !self.possible_cells(value).has(cell) {
+                            invalid_positions.push(cell);
+                            debug_assert!(false, "Invalid candidates for cell {}", cell);
                         }
                         if !self
                             .get_possible_cells_for_house_and_value(house, value)
-                            .has(cell1)
+                            .has(cell)
                         {
-                            invalid_positions.push(cell1);
-                            debug_assert!(false, "Invalid candidates for cell {}", cell1);
+                            invalid_positions.push(cell);
+                            debug_assert!(false, "Invalid candidates for cell {}", cell);
                         }
                     }
-                    continue;
-                }
-                for cell2 in house.iter().take(i) {
-                    if self.cell_value(cell1) == self.cell_value(cell2) {
-                        invalid_positions.push(cell1);
-                        debug_assert!(
-                            false,
-                            "Invalid candidates for cell {} {} {:?}",
-                            self.get_cell_name(cell1),
-                            self.get_cell_name(cell2),
-                            self.cell_value(cell2)
-                        );
-                    }
                 }
             }
 
@@ -462,9 +991,12 @@ impl SudokuSolver {
         invalid_positions
     }
 
+    /// Fills in the candidates of unfilled cells from the values of their peers. Only the cells
+    /// without any candidate are filled in, so that the eliminations made before in a sudoku that
+    /// already has candidates (e.g. built by `Sudoku::from_candidates`) are preserved.
     pub fn initialize_candidates(&mut self) {
         for cell in 0..81 {
-            if self.cell_value(cell).is_none() {
+            if self.cell_value(cell).is_none() && self.candidates(cell).is_empty() {
                 let mut candidates: HashSet<_> = (1..=9).collect();
 
                 for constraint in self.constraints_of_cell(cell).iter() {
@@ -485,35 +1017,54 @@ impl SudokuSolver {
         }
     }
 
-    pub fn apply_step(&mut self, step: &SolutionRecorder) {
-        self.candidate_cells_in_rows.take();
-        self.candidate_cells_in_columns.take();
-        self.candidate_cells_in_blocks.take();
-        self.rows_with_only_two_possible_places
-            .iter_mut()
-            .for_each(|x| {
-                x.take();
-            });
-        self.rows_with_only_two_possible_places
-            .iter_mut()
-            .for_each(|x| {
-                x.take();
-            });
-
-        let reset_possible_positions_for_cell = |this: &mut SudokuSolver, cell: CellIndex| {
-            let (row, col, block) = this.cell_position(cell);
-            let row_set = this.cells_in_rows()[row].idx();
-            let col_set = this.cells_in_columns()[col].idx();
-            let block_set = this.cells_in_blocks()[block].idx();
-            for value in 1..=9 {
-                let value_idx = value as usize - 1;
-                this.possible_positions_for_house_and_value[row_set * 9 + value_idx].take();
-                this.possible_positions_for_house_and_value[col_set * 9 + value_idx].take();
-                this.possible_positions_for_house_and_value[block_set * 9 + value_idx].take();
+    /// Same as `apply_step`, but records on each placement the candidates it removes, see
+    /// `Step::removed_candidates`.
+    pub fn apply_step_recording_removals(&mut self, step: &mut SolutionRecorder) {
+        let mut removed = FxHashSet::default();
+        for position in step.steps.iter_mut() {
+            if !matches!(position.kind, StepKind::ValueSet) {
+                continue;
             }
-        };
+            let cell = position.cell_index;
+            let own_candidates = self.candidates(cell).iter().map(|value| (cell, value));
+            let peer_candidates = self
+                .house_union_of_cell(cell)
+                .iter()
+                .filter(|&peer| self.can_fill(peer, position.value))
+                .map(|peer| (peer, position.value));
+            position.removed_candidates = own_candidates
+                .chain(peer_candidates)
+                .filter(|&candidate| removed.insert(candidate))
+                .collect();
+        }
+        self.apply_step(step);
+    }
+
+    pub fn apply_step(&mut self, step: &SolutionRecorder) {
+        self.served_steps.get_mut().clear();
+
+        // 每个数字对应一个 House 的位集合，记录候选数发生变化的 House
+        let mut dirty_houses = [0u32; 9];
+
+        let reset_possible_positions_for_cell =
+            |this: &mut SudokuSolver, dirty_houses: &mut [u32; 9], cell: CellIndex| {
+                let (row, col, block) = this.cell_position(cell);
+                let row_set = this.cells_in_rows()[row].idx();
+                let col_set = this.cells_in_columns()[col].idx();
+                let block_set = this.cells_in_blocks()[block].idx();
+                for value in 1..=9 {
+                    let value_idx = value as usize - 1;
+                    this.possible_positions_for_house_and_value[row_set * 9 + value_idx].take();
+                    this.possible_positions_for_house_and_value[col_set * 9 + value_idx].take();
+                    this.possible_positions_for_house_and_value[block_set * 9 + value_idx].take();
+                    dirty_houses[value_idx] |= 1 << row_set | 1 << col_set | 1 << block_set;
+                }
+            };
 
-        let remove_candidate = |this: &mut SudokuSolver, cell: CellIndex, value: CellValue| {
+        let remove_candidate = |this: &mut SudokuSolver,
+                                dirty_houses: &mut [u32; 9],
+                                cell: CellIndex,
+                                value: CellValue| {
             if !this.sudoku.can_fill(cell, value) {
                 return;
             }
@@ -526,22 +1077,69 @@ impl SudokuSolver {
             this.possible_positions_for_house_and_value[row_set * 9 + value_idx].take();
             this.possible_positions_for_house_and_value[col_set * 9 + value_idx].take();
             this.possible_positions_for_house_and_value[block_set * 9 + value_idx].take();
+            dirty_houses[value_idx] |= 1 << row_set | 1 << col_set | 1 << block_set;
         };
         for position in step.steps.iter() {
             match position.kind {
                 StepKind::ValueSet => {
                     let value = position.value;
-                    reset_possible_positions_for_cell(self, position.cell_index);
+                    reset_possible_positions_for_cell(self, &mut dirty_houses, position.cell_index);
                     self.sudoku.fill(position.cell_index, value);
                     self.filled_cells.add(position.cell_index);
                     self.unfilled_cells.remove(position.cell_index);
                     for cell in self.house_union_of_cell(position.cell_index).iter() {
-                        remove_candidate(self, cell, position.value);
+                        remove_candidate(self, &mut dirty_houses, cell, position.value);
                     }
                 }
                 StepKind::CandidateEliminated => {
                     for position in step.steps.iter() {
-                        remove_candidate(self, position.cell_index, position.value);
+                        remove_candidate(
+                            self,
+                            &mut dirty_houses,
+                            position.cell_index,
+                            position.value,
+                        );
+                    }
+                }
+            }
+        }
+
+        self.refresh_dirty_houses(&dirty_houses);
+    }
+
+    /// Recomputes the cached candidate cells of the houses in `dirty_houses`, indexed by value - 1
+    /// with one bit per house index, instead of dropping the caches of all the houses.
+    fn refresh_dirty_houses(&mut self, dirty_houses: &[u32; 9]) {
+        let sudoku = &self.sudoku;
+        let geometry = self.geometry();
+        for (value_idx, &dirty) in dirty_houses.iter().enumerate() {
+            if dirty == 0 {
+                continue;
+            }
+            let value = value_idx as CellValue + 1;
+            self.rows_with_only_two_possible_places[value_idx].take();
+            self.cols_with_only_two_possible_places[value_idx].take();
+
+            for (cache, houses) in [
+                (&mut self.candidate_cells_in_rows, &geometry.cells_in_rows),
+                (
+                    &mut self.candidate_cells_in_columns,
+                    &geometry.cells_in_columns,
+                ),
+                (
+                    &mut self.candidate_cells_in_blocks,
+                    &geometry.cells_in_blocks,
+                ),
+            ] {
+                let Some(cache) = cache.get_mut() else {
+                    continue;
+                };
+                for (cells, house) in cache[value_idx].iter_mut().zip(houses.iter()) {
+                    if dirty & 1 << house.idx() != 0 {
+                        *cells = NamedCellSet::from_cellset(
+                            house,
+                            sudoku.get_possible_cells(value) & house,
+                        );
                     }
                 }
             }
@@ -557,27 +1155,140 @@ impl SudokuSolver {
         true
     }
 
-    pub fn solve_one_step(&self, techniques: &Techniques) -> Option<SolutionRecorder> {
-        let mut solution = SolutionRecorder::new();
-        for technique in techniques.0.iter() {
-            technique(self, &mut solution);
-            if solution.should_return() {
-                break;
-            }
+    /// Returns the number of cells filled if the sudoku can be completed with singles only,
+    /// or `None` if singles stall before completion.
+    pub fn singles_to_complete(&self) -> Option<usize> {
+        let mut solver = SudokuSolver::new(self.take_sudoku());
+        let techniques = Techniques::from(
+            [
+                Technique::FullHouse,
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+            ]
+            .into_iter(),
+        );
+        let steps = solver.solve_to_end(&techniques);
+        if !solver.is_completed() {
+            return None;
+        }
+        Some(
+            steps
+                .iter()
+                .flat_map(|step| step.steps.iter())
+                .filter(|step| matches!(step.kind, StepKind::ValueSet))
+                .count(),
+        )
+    }
+
+    /// A cheap and conservative check of whether `technique` may find a step in the current state.
+    /// Returns false only if the technique certainly cannot find anything.
+    pub fn technique_applicable(&self, technique: Technique) -> bool {
+        if self.unfilled_cells().is_empty() {
+            return false;
+        }
+        let count_cells_with_candidates = |size: usize| {
+            self.unfilled_cells()
+                .iter()
+                .filter(|&cell| self.candidates(cell).size() == size)
+                .count()
+        };
+        match technique {
+            Technique::FullHouse => self
+                .all_constraints()
+                .iter()
+                .any(|house| (&**house & self.unfilled_cells()).size() == 1),
+            Technique::NakedSingle => count_cells_with_candidates(1) > 0,
+            Technique::ChuteRemotePair | Technique::WWing => count_cells_with_candidates(2) >= 2,
+            Technique::XYWing | Technique::XYChain => count_cells_with_candidates(2) >= 3,
+            Technique::RemotePairs => count_cells_with_candidates(2) >= 4,
+            Technique::XYZWing => {
+                count_cells_with_candidates(2) >= 2 && count_cells_with_candidates(3) >= 1
+            }
+            Technique::Exocet => {
+                self.unfilled_cells()
+                    .iter()
+                    .filter(|&cell| self.candidates(cell).size() <= 4)
+                    .count()
+                    >= 2
+            }
+            _ => true,
+        }
+    }
+
+    pub fn solve_one_step(&self, techniques: &Techniques) -> Option<SolutionRecorder> {
+        self.solve_one_step_with(techniques, SolutionRecorder::new())
+    }
+
+    /// Same as `solve_one_step`, but records the steps into `solution` so that its options
+    /// (e.g. `SolutionRecorder::set_minimal_eliminations`) take effect.
+    pub fn solve_one_step_with(
+        &self,
+        techniques: &Techniques,
+        mut solution: SolutionRecorder,
+    ) -> Option<SolutionRecorder> {
+        if self.skip_served_steps {
+            solution.ignored_steps = self.served_steps.borrow().clone();
+        }
+        solution.full_house_enabled = techniques.contains(Technique::FullHouse);
+        for technique in techniques.solver_fns.iter() {
+            technique(self, &mut solution);
+            if solution.minimal_eliminations {
+                solution.minimize_new_eliminations(self);
+            }
+            if solution.should_return() {
+                break;
+            }
         }
         if solution.is_empty() {
             return None;
         }
+        self.record_served_steps(&solution);
         return Some(solution);
     }
+
+    fn record_served_steps(&self, solution: &SolutionRecorder) {
+        if self.skip_served_steps {
+            self.served_steps.borrow_mut().extend(
+                solution
+                    .steps
+                    .iter()
+                    .map(|step| (step.technique.clone(), step.reason.clone())),
+            );
+        }
+    }
+
+    /// When enabled, repeated calls to `solve_one_step` without applying the returned steps
+    /// return different deductions, e.g. for a "next hint" button.
+    pub fn set_skip_served_steps(&mut self, enabled: bool) {
+        self.skip_served_steps = enabled;
+        self.served_steps.get_mut().clear();
+    }
+
+    pub fn reset_served_steps(&self) {
+        self.served_steps.borrow_mut().clear();
+    }
 }
 
+/// With the `serde` feature, a recorder is serialized as the list of its steps only, and
+/// deserialized into a fresh recorder holding those steps.
 #[wasm_bindgen(getter_with_clone)]
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Vec<Step>", from = "Vec<Step>")
+)]
 pub struct SolutionRecorder {
     /// If fast_mode is true, the solver will return as soon as a new step is added.
     fast_mode: bool,
     new_step_start_idx: usize,
+    /// Deductions whose steps are silently dropped when added, see
+    /// `SudokuSolver::set_skip_served_steps`.
+    ignored_steps: FxHashSet<(Technique, String)>,
+    /// If true, only the most useful elimination of each pattern is kept.
+    minimal_eliminations: bool,
+    /// If true, NakedSingle leaves the last unfilled cell of a house to FullHouse.
+    full_house_enabled: bool,
     pub steps: Vec<Step>,
 }
 
@@ -587,6 +1298,9 @@ impl SolutionRecorder {
         Self {
             fast_mode: true,
             new_step_start_idx: 0,
+            ignored_steps: FxHashSet::default(),
+            minimal_eliminations: false,
+            full_house_enabled: false,
             steps: vec![],
         }
     }
@@ -599,10 +1313,45 @@ impl SolutionRecorder {
         self.new_step_start_idx < self.steps.len()
     }
 
+    /// When enabled, a technique reporting several eliminations for one pattern only keeps a single
+    /// one, preferring an elimination that leaves its cell with a single candidate.
+    pub fn set_minimal_eliminations(&mut self, enabled: bool) {
+        self.minimal_eliminations = enabled;
+    }
+
+    pub(crate) fn minimize_new_eliminations(&mut self, sudoku: &SudokuSolver) {
+        let new_steps = &self.steps[self.new_step_start_idx..];
+        if new_steps.len() <= 1
+            || new_steps
+                .iter()
+                .any(|step| !matches!(step.kind, StepKind::CandidateEliminated))
+        {
+            return;
+        }
+        let kept = new_steps
+            .iter()
+            .position(|step| sudoku.candidates(step.cell_index).size() == 2)
+            .unwrap_or(0);
+        let kept = self.steps.swap_remove(self.new_step_start_idx + kept);
+        self.steps.truncate(self.new_step_start_idx);
+        self.steps.push(kept);
+    }
+
+    pub(crate) fn full_house_enabled(&self) -> bool {
+        self.full_house_enabled
+    }
+
     pub(crate) fn should_return(&self) -> bool {
         self.fast_mode && self.new_step_start_idx < self.steps.len()
     }
 
+    fn is_ignored(&self, technique: &Technique, reason: &str) -> bool {
+        !self.ignored_steps.is_empty()
+            && self
+                .ignored_steps
+                .contains(&(technique.clone(), reason.to_string()))
+    }
+
     pub(crate) fn add_value_set(
         &mut self,
         technique: Technique,
@@ -610,12 +1359,19 @@ impl SolutionRecorder {
         cell_index: CellIndex,
         value: CellValue,
     ) {
+        if self.is_ignored(&technique, &reason) {
+            return;
+        }
         self.steps.push(Step {
             kind: StepKind::ValueSet,
             technique,
             reason,
             cell_index,
             value,
+            fins: vec![],
+            size: 0,
+            removed_candidates: vec![],
+            highlights: vec![],
         });
     }
 
@@ -626,19 +1382,60 @@ impl SolutionRecorder {
         cell_index: CellIndex,
         value: CellValue,
     ) {
+        if self.is_ignored(&technique, &reason) {
+            return;
+        }
         self.steps.push(Step {
             kind: StepKind::CandidateEliminated,
             technique: technique,
             reason,
             cell_index,
             value,
+            fins: vec![],
+            size: 0,
+            removed_candidates: vec![],
+            highlights: vec![],
         });
     }
 
+    pub(crate) fn add_fish_elimination(
+        &mut self,
+        technique: Technique,
+        reason: String,
+        cell_index: CellIndex,
+        value: CellValue,
+        size: usize,
+        highlights: &[CellHighlight],
+    ) {
+        let len = self.steps.len();
+        self.add_elimination(technique, reason, cell_index, value);
+        if let Some(step) = self.steps.get_mut(len) {
+            step.size = size;
+            step.fins = highlights
+                .iter()
+                .filter(|highlight| highlight.role == HighlightRole::Fin)
+                .map(|highlight| highlight.cell)
+                .collect();
+            step.highlights = highlights.to_vec();
+        }
+    }
+
+    /// Attaches `highlights` to the steps added since the recorder had `len` steps.
+    pub(crate) fn highlight_steps_since(&mut self, len: usize, highlights: &[CellHighlight]) {
+        for step in self.steps.iter_mut().skip(len) {
+            step.highlights = highlights.to_vec();
+        }
+    }
+
     pub(crate) fn is_empty(&self) -> bool {
         self.steps.is_empty()
     }
 
+    /// Orders the steps by cell index and then value, i.e. in reading order of the board.
+    pub fn sort_steps_by_position(&mut self) {
+        self.steps.sort_by_key(|step| (step.cell_index, step.value));
+    }
+
     pub fn to_string(&self, sudoku: &Sudoku) -> String {
         let mut f = String::new();
         use std::fmt::Write;
@@ -672,27 +1469,143 @@ impl SolutionRecorder {
     }
 }
 
+impl From<Vec<Step>> for SolutionRecorder {
+    fn from(steps: Vec<Step>) -> Self {
+        Self {
+            steps,
+            ..Self::new()
+        }
+    }
+}
+
+impl From<SolutionRecorder> for Vec<Step> {
+    fn from(solution: SolutionRecorder) -> Self {
+        solution.steps
+    }
+}
+
+impl SolutionRecorder {
+    /// Cells that are left with a single candidate by the recorded steps while having several
+    /// candidates in `sudoku`, with their remaining candidate. Cells filled by the steps are not
+    /// included.
+    pub fn newly_forced_singles(&self, sudoku: &SudokuSolver) -> Vec<(CellIndex, CellValue)> {
+        let placed: CellSet = self
+            .steps
+            .iter()
+            .filter(|step| matches!(step.kind, StepKind::ValueSet))
+            .map(|step| step.cell_index)
+            .collect();
+        let mut remaining: HashMap<CellIndex, ValueSet> = HashMap::new();
+        for step in self.steps.iter() {
+            for (cell, value) in sudoku.candidates_removed_by(step) {
+                if placed.has(cell) {
+                    continue;
+                }
+                remaining
+                    .entry(cell)
+                    .or_insert_with(|| sudoku.candidates(cell).clone())
+                    .delete(value);
+            }
+        }
+        remaining
+            .into_iter()
+            .filter(|(cell, candidates)| {
+                candidates.size() == 1 && sudoku.candidates(*cell).size() > 1
+            })
+            .map(|(cell, candidates)| (cell, candidates.single_value()))
+            .sorted()
+            .collect()
+    }
+}
+
 #[wasm_bindgen(getter_with_clone)]
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Step {
     pub kind: StepKind,
     pub technique: Technique,
     pub reason: String,
     pub cell_index: CellIndex,
     pub value: CellValue,
+    /// The fin cells of a finned fish, so that they can be highlighted apart from the base and cover sets.
+    pub fins: Vec<CellIndex>,
+    /// The size of the pattern, i.e. the number of base sets of a fish. It is 0 for other techniques.
+    pub size: usize,
+    /// The candidates removed by a placement, i.e. all the candidates of the cell and the placed
+    /// value in its peers, so that the placement can be reverted. Only recorded by
+    /// `SudokuSolver::apply_step_recording_removals`.
+    #[wasm_bindgen(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub removed_candidates: Vec<(CellIndex, CellValue)>,
+    /// The candidates of the pattern the step is deduced from, e.g. the base, cover and fin cells of
+    /// a fish or the nodes of a chain, so that they can be highlighted. It is empty for simple
+    /// techniques.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub highlights: Vec<CellHighlight>,
 }
 
 #[wasm_bindgen]
-#[derive(Debug, Clone)]
+impl Step {
+    /// The name of the technique including the size of the pattern, e.g. "Franken Swordfish".
+    pub fn technique_name(&self) -> String {
+        self.technique.name_with_size(self.size)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StepKind {
     ValueSet,
     CandidateEliminated,
 }
 
+/// The role of a highlighted candidate in the pattern of a step.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HighlightRole {
+    /// A candidate of a fish in both its base sets and its cover sets.
+    Base,
+    /// A candidate of a fish in its cover sets but not in its base sets.
+    Cover,
+    /// A candidate of a fish in its base sets but not in its cover sets.
+    Fin,
+    /// A node of a chain.
+    ChainLink,
+}
+
+/// A candidate to highlight together with a step, see `Step::highlights`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellHighlight {
+    pub cell: CellIndex,
+    pub value: CellValue,
+    pub role: HighlightRole,
+}
+
+/// The primitive strong links that chains are built from: exactly one end of the link is true.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StrongLink {
+    /// A cell with exactly two candidates.
+    Bivalue {
+        cell: CellIndex,
+        values: (CellValue, CellValue),
+    },
+    /// A value with exactly two possible cells in a house. `house` is the index in `all_constraints`.
+    Bilocation {
+        house: usize,
+        value: CellValue,
+        cells: (CellIndex, CellIndex),
+    },
+}
+
 pub type SolverFn = fn(sudoku: &SudokuSolver, solution: &mut SolutionRecorder);
 
 #[wasm_bindgen]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Technique {
     // Single
     FullHouse,
@@ -701,16 +1614,29 @@ pub enum Technique {
 
     // Intersection
     LockedCandidates,
+    LockedCandidatesPointing,
+    LockedCandidatesClaiming,
 
     // Subset
     HiddenSubset,
     NakedSubset,
+    LockedSet,
+    RemainingCount,
+    DistributedDisjointSubset,
 
     // Fish
     BasicFish,
+    XWing,
+    Swordfish,
+    Jellyfish,
     FinnedFish,
+    SashimiFish,
     FrankenFish,
     MutantFish,
+    KrakenFish,
+
+    // Chute
+    ChuteRemotePair,
 
     // Single digit patterns
     TwoStringKite,
@@ -721,10 +1647,43 @@ pub enum Technique {
     WWing,
     XYWing,
     XYZWing,
+    WXYZWing,
+
+    // ALS
+    AlsXz,
+    DeathBlossom,
+
+    // Coloring
+    SimpleColoring,
+    /// Only the contradiction rules of 3D Medusa, a cheaper check before `Medusa3D`.
+    ThreeDimensionalMedusa,
+    /// 3D Medusa with both the contradiction and the elimination rules.
+    Medusa3D,
+
+    // Uniqueness
+    UniqueRectangle,
+    HiddenRectangle,
+    AvoidableRectangle,
+    #[allow(clippy::upper_case_acronyms)]
+    BUG,
+
+    // Exocet
+    Exocet,
+
+    // Killer
+    KillerCombinations,
 
     // Chain
+    RemotePairs,
+    XYChain,
+    GroupedXChain,
+    AicType2,
+    NiceLoop,
     ForcedChain,
 
+    // Template
+    Template,
+
     Guess,
 }
 
@@ -735,22 +1694,241 @@ impl Technique {
             Technique::NakedSingle => single::solve_naked_single,
             Technique::HiddenSingle => single::solve_hidden_single,
             Technique::LockedCandidates => intersection::solve_locked_candidates,
+            Technique::LockedCandidatesPointing => intersection::solve_locked_candidates_pointing,
+            Technique::LockedCandidatesClaiming => intersection::solve_locked_candidates_claiming,
             Technique::HiddenSubset => subset::solve_hidden_subset,
             Technique::NakedSubset => subset::solve_naked_subset,
+            Technique::LockedSet => subset::solve_locked_sets,
+            Technique::RemainingCount => subset::solve_remaining_count,
+            Technique::DistributedDisjointSubset => subset::solve_distributed_disjoint_subsets,
             Technique::BasicFish => fish::solve_basic_fish,
+            Technique::XWing => fish::solve_x_wing,
+            Technique::Swordfish => fish::solve_swordfish,
+            Technique::Jellyfish => fish::solve_jellyfish,
             Technique::FinnedFish => fish::solve_finned_fish,
+            Technique::SashimiFish => fish::solve_sashimi_fish,
             Technique::FrankenFish => fish::solve_franken_fish,
             Technique::MutantFish => fish::solve_mutant_fish,
+            Technique::KrakenFish => fish::solve_kraken_fish,
+            Technique::ChuteRemotePair => chute::solve_chute_remote_pair,
             Technique::TwoStringKite => single_digit_patterns::solve_two_string_kite,
             Technique::Skyscraper => single_digit_patterns::solve_skyscraper,
             Technique::RectangleElimination => single_digit_patterns::solve_rectangle_elimination,
             Technique::WWing => wing::solve_w_wing,
             Technique::XYWing => wing::solve_xy_wing,
             Technique::XYZWing => wing::solve_xyz_wing,
+            Technique::WXYZWing => wing::solve_wxyz_wing,
+            Technique::AlsXz => als::solve_als_xz,
+            Technique::DeathBlossom => death_blossom::solve_death_blossom,
+            Technique::SimpleColoring => single_digit_patterns::solve_simple_coloring,
+            Technique::ThreeDimensionalMedusa => medusa::solve_3d_medusa_contradiction,
+            Technique::Medusa3D => medusa::solve_3d_medusa,
+            Technique::UniqueRectangle => unique_rectangle::solve_unique_rectangle,
+            Technique::HiddenRectangle => unique_rectangle::solve_hidden_rectangle,
+            Technique::AvoidableRectangle => unique_rectangle::solve_avoidable_rectangle,
+            Technique::BUG => bug::solve_bug,
+            Technique::Exocet => exocet::solve_exocet,
+            Technique::KillerCombinations => killer::solve_killer_combinations,
+            Technique::RemotePairs => remote_pairs::solve_remote_pairs,
+            Technique::XYChain => chain::solve_xy_chain,
+            Technique::GroupedXChain => chain::solve_grouped_x_chain,
+            Technique::AicType2 => chain::solve_aic_type2,
+            Technique::NiceLoop => chain::solve_nice_loop,
             Technique::ForcedChain => chain::solve_forced_chain,
+            Technique::Template => template::solve_template,
             Technique::Guess => guess::solve_guess,
         }
     }
+
+    /// The difficulty of a single use of the technique, used by `SudokuSolver::rate`. Singles are
+    /// the cheapest, fish and chains are expensive, and guessing is the most expensive.
+    pub fn weight(&self) -> u32 {
+        match self {
+            Technique::FullHouse => 10,
+            Technique::NakedSingle => 12,
+            Technique::HiddenSingle => 15,
+            Technique::LockedCandidates
+            | Technique::LockedCandidatesPointing
+            | Technique::LockedCandidatesClaiming => 26,
+            Technique::LockedSet => 28,
+            Technique::NakedSubset => 30,
+            Technique::RemainingCount => 30,
+            Technique::HiddenSubset => 34,
+            Technique::DistributedDisjointSubset => 50,
+            Technique::BasicFish | Technique::XWing => 32,
+            Technique::Swordfish => 38,
+            Technique::Jellyfish => 52,
+            Technique::FinnedFish => 40,
+            Technique::SashimiFish => 41,
+            Technique::FrankenFish => 50,
+            Technique::MutantFish => 60,
+            Technique::KrakenFish => 68,
+            Technique::ChuteRemotePair => 45,
+            Technique::TwoStringKite => 41,
+            Technique::Skyscraper => 40,
+            Technique::RectangleElimination => 42,
+            Technique::WWing => 44,
+            Technique::XYWing => 42,
+            Technique::XYZWing => 44,
+            Technique::WXYZWing => 46,
+            Technique::AlsXz => 55,
+            Technique::DeathBlossom => 65,
+            Technique::SimpleColoring => 45,
+            Technique::ThreeDimensionalMedusa => 55,
+            Technique::Medusa3D => 55,
+            Technique::UniqueRectangle => 45,
+            Technique::HiddenRectangle => 48,
+            Technique::AvoidableRectangle => 47,
+            Technique::BUG => 56,
+            Technique::Exocet => 70,
+            Technique::KillerCombinations => 30,
+            Technique::RemotePairs => 45,
+            Technique::XYChain => 50,
+            Technique::GroupedXChain => 55,
+            Technique::AicType2 => 60,
+            Technique::NiceLoop => 60,
+            Technique::ForcedChain => 70,
+            Technique::Template => 80,
+            Technique::Guess => 100,
+        }
+    }
+
+    /// Whether the technique only runs other techniques of `Technique::all`, so that a UI listing
+    /// all the techniques can leave it out or toggle it together with them.
+    pub fn is_combined(&self) -> bool {
+        // LockedCandidatesPointing + LockedCandidatesClaiming
+        matches!(self, Technique::LockedCandidates)
+    }
+
+    /// Every technique, from the easiest to the hardest according to `weight`, including the
+    /// combined ones (see `is_combined`).
+    pub fn all() -> &'static [Technique] {
+        &[
+            Technique::FullHouse,
+            Technique::NakedSingle,
+            Technique::HiddenSingle,
+            Technique::LockedCandidates,
+            Technique::LockedCandidatesPointing,
+            Technique::LockedCandidatesClaiming,
+            Technique::LockedSet,
+            Technique::NakedSubset,
+            Technique::RemainingCount,
+            Technique::KillerCombinations,
+            Technique::BasicFish,
+            Technique::XWing,
+            Technique::HiddenSubset,
+            Technique::Swordfish,
+            Technique::FinnedFish,
+            Technique::Skyscraper,
+            Technique::SashimiFish,
+            Technique::TwoStringKite,
+            Technique::RectangleElimination,
+            Technique::XYWing,
+            Technique::WWing,
+            Technique::XYZWing,
+            Technique::ChuteRemotePair,
+            Technique::SimpleColoring,
+            Technique::UniqueRectangle,
+            Technique::RemotePairs,
+            Technique::WXYZWing,
+            Technique::AvoidableRectangle,
+            Technique::HiddenRectangle,
+            Technique::DistributedDisjointSubset,
+            Technique::FrankenFish,
+            Technique::XYChain,
+            Technique::Jellyfish,
+            Technique::AlsXz,
+            Technique::ThreeDimensionalMedusa,
+            Technique::Medusa3D,
+            Technique::GroupedXChain,
+            Technique::BUG,
+            Technique::MutantFish,
+            Technique::AicType2,
+            Technique::NiceLoop,
+            Technique::DeathBlossom,
+            Technique::KrakenFish,
+            Technique::Exocet,
+            Technique::ForcedChain,
+            Technique::Template,
+            Technique::Guess,
+        ]
+    }
+
+    /// The canonical name of the technique, which is accepted by `Technique::from`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::FullHouse => "full_house",
+            Technique::NakedSingle => "naked_single",
+            Technique::HiddenSingle => "hidden_single",
+            Technique::LockedCandidates => "locked_candidates",
+            Technique::LockedCandidatesPointing => "locked_candidates_pointing",
+            Technique::LockedCandidatesClaiming => "locked_candidates_claiming",
+            Technique::HiddenSubset => "hidden_subset",
+            Technique::NakedSubset => "naked_subset",
+            Technique::LockedSet => "locked_set",
+            Technique::RemainingCount => "remaining_count",
+            Technique::DistributedDisjointSubset => "distributed_disjoint_subset",
+            Technique::BasicFish => "basic_fish",
+            Technique::XWing => "x_wing",
+            Technique::Swordfish => "swordfish",
+            Technique::Jellyfish => "jellyfish",
+            Technique::FinnedFish => "finned_fish",
+            Technique::SashimiFish => "sashimi_fish",
+            Technique::FrankenFish => "franken_fish",
+            Technique::MutantFish => "mutant_fish",
+            Technique::KrakenFish => "kraken_fish",
+            Technique::ChuteRemotePair => "chute_remote_pair",
+            Technique::TwoStringKite => "two_string_kite",
+            Technique::Skyscraper => "skyscraper",
+            Technique::RectangleElimination => "rectangle_elimination",
+            Technique::WWing => "w_wing",
+            Technique::XYWing => "xy_wing",
+            Technique::XYZWing => "xyz_wing",
+            Technique::WXYZWing => "wxyz_wing",
+            Technique::AlsXz => "als_xz",
+            Technique::DeathBlossom => "death_blossom",
+            Technique::SimpleColoring => "simple_coloring",
+            Technique::ThreeDimensionalMedusa => "three_dimensional_medusa",
+            Technique::Medusa3D => "medusa_3d",
+            Technique::UniqueRectangle => "unique_rectangle",
+            Technique::HiddenRectangle => "hidden_rectangle",
+            Technique::AvoidableRectangle => "avoidable_rectangle",
+            Technique::BUG => "bug",
+            Technique::Exocet => "exocet",
+            Technique::KillerCombinations => "killer_combinations",
+            Technique::RemotePairs => "remote_pairs",
+            Technique::XYChain => "xy_chain",
+            Technique::GroupedXChain => "grouped_x_chain",
+            Technique::AicType2 => "aic_type2",
+            Technique::NiceLoop => "nice_loop",
+            Technique::ForcedChain => "forced_chain",
+            Technique::Template => "template",
+            Technique::Guess => "guess",
+        }
+    }
+
+    /// The name of the technique for a pattern of `size`, e.g. "Franken Swordfish" for a
+    /// `FrankenFish` with 3 base sets. Techniques without size-specific names use the variant name.
+    pub fn name_with_size(&self, size: usize) -> String {
+        let fish_name = match size {
+            2 => "X-Wing",
+            3 => "Swordfish",
+            4 => "Jellyfish",
+            _ => return format!("{:?}", self),
+        };
+        match self {
+            Technique::BasicFish
+            | Technique::XWing
+            | Technique::Swordfish
+            | Technique::Jellyfish => fish_name.to_string(),
+            Technique::FinnedFish => format!("Finned {}", fish_name),
+            Technique::SashimiFish => format!("Sashimi {}", fish_name),
+            Technique::FrankenFish => format!("Franken {}", fish_name),
+            Technique::MutantFish => format!("Mutant {}", fish_name),
+            Technique::KrakenFish => format!("Kraken {}", fish_name),
+            _ => format!("{:?}", self),
+        }
+    }
 }
 
 impl<S: AsRef<str> + Display> From<S> for Technique {
@@ -765,20 +1943,43 @@ impl<S: AsRef<str> + Display> From<S> for Technique {
 
             "LockedCandidates" => Technique::LockedCandidates,
             "locked_candidates" => Technique::LockedCandidates,
+            "LockedCandidatesPointing" => Technique::LockedCandidatesPointing,
+            "locked_candidates_pointing" => Technique::LockedCandidatesPointing,
+            "LockedCandidatesClaiming" => Technique::LockedCandidatesClaiming,
+            "locked_candidates_claiming" => Technique::LockedCandidatesClaiming,
 
             "HiddenSubset" => Technique::HiddenSubset,
             "hidden_subset" => Technique::HiddenSubset,
             "NakedSubset" => Technique::NakedSubset,
             "naked_subset" => Technique::NakedSubset,
+            "LockedSet" => Technique::LockedSet,
+            "locked_set" => Technique::LockedSet,
+            "RemainingCount" => Technique::RemainingCount,
+            "remaining_count" => Technique::RemainingCount,
+            "DistributedDisjointSubset" => Technique::DistributedDisjointSubset,
+            "distributed_disjoint_subset" => Technique::DistributedDisjointSubset,
 
             "BasicFish" => Technique::BasicFish,
             "basic_fish" => Technique::BasicFish,
+            "XWing" => Technique::XWing,
+            "x_wing" => Technique::XWing,
+            "Swordfish" => Technique::Swordfish,
+            "swordfish" => Technique::Swordfish,
+            "Jellyfish" => Technique::Jellyfish,
+            "jellyfish" => Technique::Jellyfish,
             "FinnedFish" => Technique::FinnedFish,
             "finned_fish" => Technique::FinnedFish,
+            "SashimiFish" => Technique::SashimiFish,
+            "sashimi_fish" => Technique::SashimiFish,
             "FrankenFish" => Technique::FrankenFish,
             "franken_fish" => Technique::FrankenFish,
             "MutantFish" => Technique::MutantFish,
             "mutant_fish" => Technique::MutantFish,
+            "KrakenFish" => Technique::KrakenFish,
+            "kraken_fish" => Technique::KrakenFish,
+
+            "ChuteRemotePair" => Technique::ChuteRemotePair,
+            "chute_remote_pair" => Technique::ChuteRemotePair,
 
             "TwoStringKite" => Technique::TwoStringKite,
             "two_string_kite" => Technique::TwoStringKite,
@@ -793,9 +1994,51 @@ impl<S: AsRef<str> + Display> From<S> for Technique {
             "xy_wing" => Technique::XYWing,
             "XYZWing" => Technique::XYZWing,
             "xyz_wing" => Technique::XYZWing,
-
+            "WXYZWing" => Technique::WXYZWing,
+            "wxyz_wing" => Technique::WXYZWing,
+
+            "AlsXz" => Technique::AlsXz,
+            "als_xz" => Technique::AlsXz,
+            "DeathBlossom" => Technique::DeathBlossom,
+            "death_blossom" => Technique::DeathBlossom,
+
+            "SimpleColoring" => Technique::SimpleColoring,
+            "simple_coloring" => Technique::SimpleColoring,
+            "ThreeDimensionalMedusa" => Technique::ThreeDimensionalMedusa,
+            "three_dimensional_medusa" => Technique::ThreeDimensionalMedusa,
+
+            "Medusa3D" => Technique::Medusa3D,
+            "medusa_3d" => Technique::Medusa3D,
+
+            "UniqueRectangle" => Technique::UniqueRectangle,
+            "unique_rectangle" => Technique::UniqueRectangle,
+            "HiddenRectangle" => Technique::HiddenRectangle,
+            "hidden_rectangle" => Technique::HiddenRectangle,
+            "AvoidableRectangle" => Technique::AvoidableRectangle,
+            "avoidable_rectangle" => Technique::AvoidableRectangle,
+            "BUG" => Technique::BUG,
+            "bug" => Technique::BUG,
+
+            "Exocet" => Technique::Exocet,
+            "exocet" => Technique::Exocet,
+
+            "KillerCombinations" => Technique::KillerCombinations,
+            "killer_combinations" => Technique::KillerCombinations,
+
+            "RemotePairs" => Technique::RemotePairs,
+            "remote_pairs" => Technique::RemotePairs,
+            "XYChain" => Technique::XYChain,
+            "xy_chain" => Technique::XYChain,
+            "GroupedXChain" => Technique::GroupedXChain,
+            "grouped_x_chain" => Technique::GroupedXChain,
+            "AicType2" => Technique::AicType2,
+            "aic_type2" => Technique::AicType2,
+            "NiceLoop" => Technique::NiceLoop,
+            "nice_loop" => Technique::NiceLoop,
             "ForcedChain" => Technique::ForcedChain,
             "forced_chain" => Technique::ForcedChain,
+            "Template" => Technique::Template,
+            "template" => Technique::Template,
 
             "guess" => Technique::Guess,
             "Guess" => Technique::Guess,
@@ -807,14 +2050,22 @@ impl<S: AsRef<str> + Display> From<S> for Technique {
 
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
-pub struct Techniques(Vec<fn(sudoku: &SudokuSolver, solution: &mut SolutionRecorder)>);
+pub struct Techniques {
+    techniques: Vec<Technique>,
+    solver_fns: Vec<SolverFn>,
+}
 
 impl Techniques {
     pub fn new() -> Self {
-        let default_techniques = [
+        Self::from(Self::default_technique_list().into_iter())
+    }
+
+    pub fn default_technique_list() -> Vec<Technique> {
+        vec![
             Technique::NakedSingle,
             Technique::HiddenSingle,
-            Technique::LockedCandidates,
+            Technique::LockedCandidatesPointing,
+            Technique::LockedCandidatesClaiming,
             Technique::HiddenSubset,
             Technique::NakedSubset,
             Technique::TwoStringKite,
@@ -829,16 +2080,19 @@ impl Techniques {
 
             Technique::ForcedChain,
             Technique::Guess,
-        ];
-        Self::from(default_techniques.into_iter())
+        ]
     }
 
     pub fn from(techniques: impl Iterator<Item = impl Into<Technique>>) -> Self {
-        let mut funcs: Vec<SolverFn> = vec![];
-        for technique in techniques {
-            funcs.push(technique.into().solver_fn());
+        let techniques: Vec<Technique> = techniques.map(|technique| technique.into()).collect();
+        let solver_fns = techniques
+            .iter()
+            .map(|technique| technique.solver_fn())
+            .collect();
+        Self {
+            techniques,
+            solver_fns,
         }
-        Self(funcs)
     }
 }
 
@@ -849,10 +2103,1188 @@ impl Techniques {
     }
 
     pub fn from_slice(techniques: Vec<Technique>) -> Self {
-        let mut funcs: Vec<SolverFn> = vec![];
-        for technique in techniques {
-            funcs.push(technique.solver_fn());
+        Self::from(techniques.into_iter())
+    }
+
+    /// A copy of the techniques with `technique` appended, unless it is already included.
+    pub fn with(&self, technique: Technique) -> Self {
+        if self.techniques.contains(&technique) {
+            return self.clone();
+        }
+        let mut techniques = self.clone();
+        techniques.solver_fns.push(technique.solver_fn());
+        techniques.techniques.push(technique);
+        techniques
+    }
+
+    /// A copy of the techniques with `technique` removed.
+    pub fn without(&self, technique: Technique) -> Self {
+        Self::from(self.techniques.iter().filter(|&t| *t != technique).cloned())
+    }
+
+    /// Whether `technique` is enabled.
+    pub fn contains(&self, technique: Technique) -> bool {
+        self.techniques.contains(&technique)
+    }
+}
+
+/// A problem found by `SudokuSolver::validate` in `cells`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    pub cells: Vec<CellIndex>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// `value` is filled in all the cells of the house.
+    DuplicateValue {
+        house_name: String,
+        value: CellValue,
+    },
+    /// The single cell has no candidate left.
+    NoCandidates,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ConflictKind::DuplicateValue { house_name, value } => {
+                write!(f, "{} has {} {}s", house_name, self.cells.len(), value)
+            }
+            ConflictKind::NoCandidates => {
+                let cell = self.cells[0];
+                write!(f, "r{}c{} has no candidate", cell / 9 + 1, cell % 9 + 1)
+            }
+        }
+    }
+}
+
+/// The next logical move, see `SudokuSolver::hint`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hint {
+    pub technique: Technique,
+    /// The name of the technique including the size of the pattern, see `Step::technique_name`.
+    pub technique_name: String,
+    pub kind: StepKind,
+    pub reason: String,
+    /// The values set or the candidates eliminated by the move.
+    pub candidates: Vec<(CellIndex, CellValue)>,
+}
+
+/// The difficulty of a sudoku, see `SudokuSolver::rate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DifficultyRating {
+    /// The technique with the largest weight among the techniques used.
+    pub hardest_technique: Technique,
+    /// The sum of the weights of all the steps.
+    pub score: u32,
+    pub needs_guessing: bool,
+}
+
+impl DifficultyRating {
+    pub fn tier(&self) -> DifficultyTier {
+        DifficultyTier::from_technique(&self.hardest_technique)
+    }
+}
+
+/// A coarse difficulty of a sudoku, from the hardest technique needed to solve it.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DifficultyTier {
+    /// Singles only.
+    Easy,
+    /// Locked candidates and subsets.
+    Medium,
+    /// Basic fish, wings, single digit patterns and uniqueness.
+    Hard,
+    /// Chains, ALSes and advanced fish.
+    Expert,
+    /// Forcing chains, templates and guessing.
+    Extreme,
+}
+
+impl DifficultyTier {
+    pub fn from_technique(technique: &Technique) -> Self {
+        match technique.weight() {
+            0..=15 => DifficultyTier::Easy,
+            16..=34 => DifficultyTier::Medium,
+            35..=49 => DifficultyTier::Hard,
+            50..=69 => DifficultyTier::Expert,
+            _ => DifficultyTier::Extreme,
+        }
+    }
+
+    /// The number of tiers between the two tiers.
+    pub fn distance(&self, other: DifficultyTier) -> usize {
+        (*self as usize).abs_diff(other as usize)
+    }
+}
+
+/// For each technique of the default set except `Guess`, counts the puzzles that the default set
+/// solves by logic but can no longer be solved when this technique is removed.
+pub fn technique_necessity(puzzles: &[Sudoku]) -> HashMap<Technique, usize> {
+    let logic_techniques = Techniques::new().without(Technique::Guess);
+    let mut necessity: HashMap<Technique, usize> = logic_techniques
+        .techniques
+        .iter()
+        .map(|technique| (technique.clone(), 0))
+        .collect();
+
+    for puzzle in puzzles {
+        let solver = SudokuSolver::new(puzzle.clone());
+        if !solver.is_solved_by_logic(&logic_techniques) {
+            continue;
+        }
+        for technique in logic_techniques.techniques.iter() {
+            if !solver.is_solved_by_logic(&logic_techniques.without(technique.clone())) {
+                *necessity.get_mut(technique).unwrap() += 1;
+            }
+        }
+    }
+    necessity
+}
+
+/// Checks that `puzzle` is completely solved by the techniques named in `techniques`, applying
+/// steps the same way as the regression tests do. On failure, describes where the solver got stuck.
+pub fn assert_solves_with(puzzle: &str, techniques: &[&str]) -> Result<(), String> {
+    let sudoku = Sudoku::try_from_values(puzzle).map_err(|err| err.to_string())?;
+    let mut solver = SudokuSolver::new(sudoku);
+    solver.initialize_candidates();
+    let techniques = Techniques::from(techniques.iter());
+    let steps = solver.solve_to_end(&techniques);
+    if solver.is_completed() && solver.get_invalid_positions().is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "{} after {} steps\n{}",
+        describe_stuck_state(&solver),
+        steps.len(),
+        solver.sudoku().to_candidate_string()
+    ))
+}
+
+fn describe_stuck_state(solver: &SudokuSolver) -> String {
+    let invalid_positions = solver.get_invalid_positions();
+    if !invalid_positions.is_empty() {
+        return format!(
+            "contradiction in {}",
+            solver.get_cellset_string(&invalid_positions.into_iter().collect())
+        );
+    }
+    let empty_cells: CellSet = solver
+        .unfilled_cells()
+        .iter()
+        .filter(|&cell| solver.candidates(cell).is_empty())
+        .collect();
+    if !empty_cells.is_empty() {
+        return format!(
+            "no candidate left in {}",
+            solver.get_cellset_string(&empty_cells)
+        );
+    }
+    format!(
+        "no step found with {} unfilled cells",
+        solver.unfilled_cells().size()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn load_solver(values: &str) -> SudokuSolver {
+        let mut solver = SudokuSolver::new(Sudoku::from_values(values));
+        solver.initialize_candidates();
+        solver
+    }
+
+    #[test]
+    fn test_find_wrong_and_missing_candidates() {
+        let mut solver = load_solver(PUZZLE);
+        let solution = Sudoku::from_values(SOLUTION);
+        // r1c3 has candidates 1,2,4 and the solution is 4
+        let wrong_candidates = solver.find_wrong_candidates(&solution);
+        assert!(wrong_candidates.contains(&(2, 1)));
+        assert!(wrong_candidates.contains(&(2, 2)));
+        assert!(!wrong_candidates.contains(&(2, 4)));
+        assert!(solver.find_missing_candidates(&solution).is_empty());
+
+        let mut step = SolutionRecorder::new();
+        step.add_elimination(Technique::Guess, "mistake".to_string(), 2, 4);
+        solver.apply_step(&step);
+        assert_eq!(solver.find_missing_candidates(&solution), vec![(2, 4)]);
+    }
+
+    #[test]
+    fn test_full_house_before_naked_single() {
+        let mut values = SOLUTION.to_string();
+        values.replace_range(0..1, ".");
+        let solver = load_solver(&values);
+        let techniques =
+            Techniques::from([Technique::NakedSingle, Technique::FullHouse].into_iter());
+        let step = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(step.steps.len(), 1);
+        assert_eq!(step.steps[0].technique, Technique::FullHouse);
+        assert_eq!((step.steps[0].cell_index, step.steps[0].value), (0, 5));
+
+        let hints = solver.ranked_hints(&techniques);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].technique, Technique::FullHouse);
+
+        // Without FullHouse, the cell is still a naked single
+        let techniques = Techniques::from([Technique::NakedSingle].into_iter());
+        let step = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(step.steps[0].technique, Technique::NakedSingle);
+    }
+
+    #[test]
+    fn test_singles_to_complete() {
+        let solver = load_solver(PUZZLE);
+        assert_eq!(solver.singles_to_complete(), Some(51));
+        assert!(!solver.is_completed());
+
+        let solver = load_solver(
+            "4.2..36..8....7..3....4.5.8...36.......4.98..6.9..2......1....5.5.2.476.37.....8.",
+        );
+        assert_eq!(solver.singles_to_complete(), None);
+    }
+
+    fn eliminations_of(
+        solver: &SudokuSolver,
+        solver_fns: &[SolverFn],
+    ) -> HashSet<(CellIndex, CellValue)> {
+        let mut solution = SolutionRecorder {
+            fast_mode: false,
+            ..SolutionRecorder::new()
+        };
+        for solver_fn in solver_fns {
+            solver_fn(solver, &mut solution);
+        }
+        solution
+            .steps
+            .iter()
+            .map(|step| (step.cell_index, step.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_locked_sets() {
+        let solver = load_solver(PUZZLE);
+        let naked = eliminations_of(&solver, &[subset::solve_naked_subset]);
+        let hidden = eliminations_of(&solver, &[subset::solve_hidden_subset]);
+        assert!(!naked.is_empty());
+        assert!(!hidden.is_empty());
+        let locked_sets = eliminations_of(&solver, &[subset::solve_locked_sets]);
+        assert_eq!(locked_sets, &naked | &hidden);
+    }
+
+    #[test]
+    fn test_remaining_count() {
+        let solver = load_solver(PUZZLE);
+        let hidden = eliminations_of(&solver, &[subset::solve_hidden_subset]);
+        assert!(!hidden.is_empty());
+        let remaining_count = eliminations_of(&solver, &[subset::solve_remaining_count]);
+        assert_eq!(remaining_count, hidden);
+    }
+
+    #[test]
+    fn test_export_and_import_state() {
+        let mut solver = load_solver(PUZZLE);
+        let mut step = SolutionRecorder::new();
+        step.add_elimination(Technique::Guess, "manual edit".to_string(), 2, 1);
+        solver.apply_step(&step);
+        let state = solver.export_state();
+
+        let mut restored = load_solver(&".".repeat(81));
+        restored.import_state(&state).unwrap();
+        assert_eq!(restored.export_state(), state);
+        assert_eq!(
+            restored.sudoku().to_candidate_string(),
+            solver.sudoku().to_candidate_string()
+        );
+        assert!(!restored.candidates(2).has(1));
+        assert!(restored.import_state("invalid").is_err());
+    }
+
+    #[test]
+    fn test_sort_steps_by_position() {
+        let mut solution = SolutionRecorder::new();
+        solution.add_elimination(Technique::Guess, "c".to_string(), 40, 3);
+        solution.add_elimination(Technique::Guess, "a".to_string(), 2, 7);
+        solution.add_elimination(Technique::Guess, "b".to_string(), 40, 1);
+        solution.add_value_set(Technique::Guess, "d".to_string(), 2, 4);
+        solution.sort_steps_by_position();
+        assert_eq!(
+            solution
+                .steps
+                .iter()
+                .map(|step| (step.cell_index, step.value, step.reason.as_str()))
+                .collect_vec(),
+            vec![(2, 4, "d"), (2, 7, "a"), (40, 1, "b"), (40, 3, "c")]
+        );
+    }
+
+    #[test]
+    fn test_minimal_eliminations() {
+        let sudoku = Sudoku::from_candidates(
+            "
+            8 47 9 3 2 5 16 47 16
+            45 6 345 47 8 1 3457 2 9
+            145 2457 12345 47 6 9 34578 4578 3478
+            156 8 125 9 4 7 126 3 16
+            1469 24 124 28 5 3 124678 4789 478
+            49 3 7 28 1 6 248 489 5
+            3 45 6 1 7 8 9 45 2
+            2 1 8 5 9 4 37 6 37
+            7 9 45 6 3 2 458 1 48
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let techniques = Techniques::from(["basic_fish"].into_iter());
+
+        let full = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(full.steps.len(), 5);
+
+        let mut solution = SolutionRecorder::new();
+        solution.set_minimal_eliminations(true);
+        let minimal = solver.solve_one_step_with(&techniques, solution).unwrap();
+        assert_eq!(
+            minimal.to_string(solver.sudoku()).trim(),
+            "[BasicFish] for 4, r1,r7 is covered by c2,c8 => r5c2<>4"
+        );
+    }
+
+    #[test]
+    fn test_fins_of_finned_fish() {
+        let sudoku = Sudoku::from_candidates(
+            "
+            3 147 9 1256 256 1257 12 28 1248
+            5 14 6 8 9 12 7 23 1234
+            78 178 2 4 3 17 6 9 5
+            12 9 7 3 4 125 8 256 126
+            4 5 8 126 26 9 3 7 12
+            12 6 3 1257 257 8 125 4 9
+            9 3 1 25 258 6 4 258 7
+            78 278 5 27 1 4 9 36 36
+            6 27 4 9 2578 3 25 1 28
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let techniques = Techniques::from(["finned_fish"].into_iter());
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            solution.to_string(solver.sudoku()).trim(),
+            "[FinnedFish] for 1, c1,c4,c7 is covered by r1,r4,r6 with fins r5c4 => r4c6<>1"
+        );
+        assert_eq!(solution.steps[0].fins, vec![solver.cell_index(4, 3)]);
+    }
+
+    #[test]
+    fn test_fish_highlights() {
+        let sudoku = Sudoku::from_candidates(
+            "
+            9 2 7 16 16 3 5 4 8
+            5 3 8 9 7 4 1 2 6
+            6 1 4 58 2 58 9 7 3
+            2 78 1 5678 4568 5678 3 9 47
+            47 9 3 2 14 17 6 8 5
+            478 6 5 78 3 9 2 1 47
+            1 4 2 3 58 578 78 6 9
+            3 78 9 1678 168 1678 4 5 2
+            78 5 6 4 9 2 78 3 1
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let techniques = Techniques::from(["basic_fish"].into_iter());
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        let step = &solution.steps[0];
+        assert_eq!(step.reason, "for 7, c2,c4,c9 is covered by r4,r6,r8");
+        let cells_of = |role: HighlightRole| {
+            step.highlights
+                .iter()
+                .filter(|highlight| highlight.role == role)
+                .map(|highlight| {
+                    assert_eq!(highlight.value, 7);
+                    solver.get_cell_name(highlight.cell)
+                })
+                .join(",")
+        };
+        assert_eq!(
+            cells_of(HighlightRole::Base),
+            "r4c2,r4c4,r4c9,r6c4,r6c9,r8c2,r8c4"
+        );
+        assert_eq!(cells_of(HighlightRole::Cover), "r4c6,r6c1,r8c6");
+        assert_eq!(cells_of(HighlightRole::Fin), "");
+
+        let solver = load_solver(PUZZLE);
+        let techniques = Techniques::from(["hidden_single"].into_iter());
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        assert!(solution.steps[0].highlights.is_empty());
+    }
+
+    #[test]
+    fn test_candidates_removed_by() {
+        let mut solver = load_solver(PUZZLE);
+        let techniques = Techniques::from(["hidden_single", "locked_candidates"].into_iter());
+        for _ in 0..10 {
+            let solution = solver.solve_one_step(&techniques).unwrap();
+            let step = &solution.steps[0];
+            let mut preview = solver.candidates_removed_by(step);
+
+            let mut single_step = SolutionRecorder::new();
+            single_step.steps.push(step.clone());
+            let mut applied = SudokuSolver::new(solver.take_sudoku());
+            applied.apply_step(&single_step);
+            let mut removed = vec![];
+            for cell in 0..81 {
+                for value in solver.candidates(cell).iter() {
+                    let placed = step.kind == StepKind::ValueSet
+                        && (cell, value) == (step.cell_index, step.value);
+                    if !applied.can_fill(cell, value) && !placed {
+                        removed.push((cell, value));
+                    }
+                }
+            }
+
+            preview.sort();
+            assert!(!preview.is_empty());
+            assert_eq!(preview, removed);
+            solver.apply_step(&solution);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_solve_one_step_parallel() {
+        let techniques = Techniques::new();
+        let mut solver = load_solver(PUZZLE);
+        while !solver.is_completed() {
+            let sequential = solver.solve_one_step(&techniques).unwrap();
+            let parallel = solver.solve_one_step_parallel(&techniques).unwrap();
+            assert_eq!(
+                parallel.to_string(solver.sudoku()),
+                sequential.to_string(solver.sudoku())
+            );
+            solver.apply_step(&sequential);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_solution_recorder() {
+        let techniques = Techniques::new();
+        let mut solver = load_solver(PUZZLE);
+        while !solver.is_completed() {
+            let solution = solver.solve_one_step(&techniques).unwrap();
+            let json = serde_json::to_string(&solution).unwrap();
+            let restored: SolutionRecorder = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored.steps, solution.steps);
+            solver.apply_step(&restored);
+        }
+    }
+
+    #[test]
+    fn test_strong_links() {
+        let solver = load_solver(PUZZLE);
+        let links = solver.strong_links();
+        let bivalue_count = links
+            .iter()
+            .filter(|link| matches!(link, StrongLink::Bivalue { .. }))
+            .count();
+        assert_eq!(bivalue_count, 13);
+        assert_eq!(links.len() - bivalue_count, 41);
+        // r1c4 can only be 2 or 6
+        assert!(links.contains(&StrongLink::Bivalue {
+            cell: 3,
+            values: (2, 6),
+        }));
+        // 1 can only be in r1c3 or r3c1 in b1
+        assert!(links.contains(&StrongLink::Bilocation {
+            house: 0,
+            value: 1,
+            cells: (2, 18),
+        }));
+    }
+
+    #[test]
+    fn test_revert_to_consistent() {
+        let mut solver = load_solver(PUZZLE);
+        let candidates = solver.sudoku().to_candidate_string();
+        let solution = Sudoku::from_values(SOLUTION);
+
+        // r1c3 should be 4
+        let mut step = SolutionRecorder::new();
+        step.add_value_set(Technique::Guess, "".to_string(), 2, 2);
+        solver.apply_step(&step);
+        assert_eq!(solver.sudoku().diff(&solution), vec![2]);
+
+        solver.revert_to_consistent(&solution);
+        assert!(solver.sudoku().diff(&solution).is_empty());
+        assert_eq!(solver.sudoku().to_candidate_string(), candidates);
+    }
+
+    #[test]
+    fn test_technique_all() {
+        let all = Technique::all();
+        assert_eq!(all.iter().unique().count(), all.len());
+        assert!(all
+            .iter()
+            .tuple_windows()
+            .all(|(easier, harder)| easier.weight() <= harder.weight()));
+        assert_eq!(all.first(), Some(&Technique::FullHouse));
+        assert_eq!(all.last(), Some(&Technique::Guess));
+        for technique in all {
+            assert_eq!(&Technique::from(technique.name()), technique);
+        }
+        assert_eq!(
+            all.iter()
+                .filter(|technique| technique.is_combined())
+                .collect_vec(),
+            vec![&Technique::LockedCandidates]
+        );
+        assert_eq!(
+            Technique::ThreeDimensionalMedusa.name(),
+            "three_dimensional_medusa"
+        );
+        assert_eq!(Technique::from("Medusa3D"), Technique::Medusa3D);
+    }
+
+    #[test]
+    fn test_ranked_hints() {
+        let solver = load_solver(PUZZLE);
+        let techniques = Techniques::from(
+            [
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+                Technique::LockedCandidates,
+            ]
+            .into_iter(),
+        );
+        let hints = solver.ranked_hints(&techniques);
+        let easiest = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(hints[0].technique, easiest.steps[0].technique);
+        assert_eq!(hints[0].technique, Technique::NakedSingle);
+        assert!(hints
+            .iter()
+            .any(|step| step.technique == Technique::HiddenSingle));
+        // Easier techniques come first
+        let rank = |technique: &Technique| match technique {
+            Technique::NakedSingle => 0,
+            Technique::HiddenSingle => 1,
+            _ => 2,
+        };
+        assert!(hints
+            .iter()
+            .tuple_windows()
+            .all(|(a, b)| rank(&a.technique) <= rank(&b.technique)));
+        assert!(hints
+            .iter()
+            .map(|step| (step.kind, step.cell_index, step.value))
+            .all_unique());
+
+        // The order of `techniques` does not matter
+        let reversed = Techniques::from(
+            [
+                Technique::LockedCandidates,
+                Technique::HiddenSingle,
+                Technique::NakedSingle,
+            ]
+            .into_iter(),
+        );
+        let reversed_hints = solver.ranked_hints(&reversed);
+        assert_eq!(reversed_hints[0].technique, Technique::NakedSingle);
+        assert!(reversed_hints
+            .iter()
+            .tuple_windows()
+            .all(|(a, b)| a.technique.weight() <= b.technique.weight()));
+        let deductions = |hints: &[Step]| {
+            hints
+                .iter()
+                .map(|step| (step.technique.clone(), step.cell_index, step.value))
+                .collect::<FxHashSet<_>>()
+        };
+        assert_eq!(deductions(&hints), deductions(&reversed_hints));
+    }
+
+    #[test]
+    fn test_technique_necessity() {
+        let puzzles = [
+            Sudoku::from_values(PUZZLE),
+            Sudoku::from_values(
+                "8..6.27.........3.6......58...1.....95...8....619..4.......3..47.3....9.529.1....",
+            ),
+        ];
+        let necessity = technique_necessity(&puzzles);
+        assert_eq!(
+            necessity.len(),
+            Techniques::default_technique_list().len() - 1
+        );
+        // The first puzzle only needs singles, which can replace each other, and the second one
+        // cannot be solved without forced chains
+        assert_eq!(necessity[&Technique::ForcedChain], 1);
+        assert_eq!(necessity.values().sum::<usize>(), 1);
+    }
+
+    #[test]
+    fn test_const_house_tables() {
+        let solver = load_solver(PUZZLE);
+        for (house, &bitset) in solver.all_constraints().iter().zip(HOUSE_BITSETS.iter()) {
+            let cells = (0..81)
+                .filter(|&cell| bitset & (1 << cell) != 0)
+                .collect_vec();
+            assert_eq!(cells.len(), 9);
+            let (rows, cols): (Vec<_>, Vec<_>) =
+                cells.iter().map(|cell| (cell / 9, cell % 9)).unzip();
+            let expected_name = if rows.iter().all_equal() {
+                format!("r{}", rows[0] + 1)
+            } else if cols.iter().all_equal() {
+                format!("c{}", cols[0] + 1)
+            } else {
+                assert!(cells
+                    .iter()
+                    .map(|cell| (cell / 27, cell % 9 / 3))
+                    .all_equal());
+                format!("b{}", rows[0] / 3 * 3 + cols[0] / 3 + 1)
+            };
+            assert_eq!(house.name(), expected_name);
+        }
+
+        for cell in 0..81u8 {
+            let peers: CellSet = (0..81u8)
+                .filter(|&other| {
+                    other != cell
+                        && (other / 9 == cell / 9
+                            || other % 9 == cell % 9
+                            || (other / 27, other % 9 / 3) == (cell / 27, cell % 9 / 3))
+                })
+                .collect();
+            assert_eq!(peers.size(), 20);
+            assert_eq!(solver.house_union_of_cell(cell), &peers);
+            assert_eq!(
+                &CellSet::from_bitset_const(PEER_BITSETS[cell as usize]),
+                &peers
+            );
+        }
+    }
+
+    #[test]
+    fn test_cell_positions() {
+        let solver = load_solver(PUZZLE);
+        for cell in 0..81u8 {
+            let expected = (
+                cell as usize / 9,
+                cell as usize % 9,
+                (cell / 27 * 3 + cell % 9 / 3) as usize,
+            );
+            assert_eq!(solver.cell_position(cell), expected);
+            assert!(solver.cells_in_blocks()[expected.2].has(cell));
+        }
+    }
+
+    #[test]
+    fn test_shared_geometry() {
+        let solver1 = load_solver(PUZZLE);
+        let solver2 = load_solver(SOLUTION);
+        assert!(std::ptr::eq(
+            solver1.all_constraints(),
+            solver2.all_constraints()
+        ));
+        assert!(std::ptr::eq(
+            solver1.house_union_of_cell(40),
+            solver2.house_union_of_cell(40)
+        ));
+    }
+
+    #[test]
+    fn test_removed_candidates_of_placement() {
+        let mut solver = load_solver(PUZZLE);
+        let before = solver.take_sudoku();
+        // r5c5 has the only candidate 5
+        let mut step = SolutionRecorder::new();
+        step.add_value_set(Technique::NakedSingle, String::new(), 40, 5);
+        solver.apply_step_recording_removals(&mut step);
+
+        let removed = &step.steps[0].removed_candidates;
+        assert_eq!(removed[0], (40, 5));
+        let expected_peers = solver
+            .house_union_of_cell(40)
+            .iter()
+            .filter(|&peer| before.can_fill(peer, 5))
+            .map(|peer| (peer, 5))
+            .collect_vec();
+        assert!(!expected_peers.is_empty());
+        assert_eq!(removed[1..], expected_peers[..]);
+        for &(cell, value) in removed.iter() {
+            assert!(before.can_fill(cell, value));
+            assert!(!solver.can_fill(cell, value));
+        }
+    }
+
+    #[test]
+    fn test_newly_forced_singles() {
+        let solver = load_solver(PUZZLE);
+        let cell_with_candidates = |size: usize| {
+            solver
+                .unfilled_cells()
+                .iter()
+                .find(|&cell| solver.candidates(cell).size() == size)
+                .unwrap()
+        };
+
+        let bivalue_cell = cell_with_candidates(2);
+        let values = solver.candidates(bivalue_cell).values().to_vec();
+        let mut solution = SolutionRecorder::new();
+        solution.add_elimination(
+            Technique::LockedCandidates,
+            String::new(),
+            bivalue_cell,
+            values[0],
+        );
+        assert_eq!(
+            solution.newly_forced_singles(&solver),
+            vec![(bivalue_cell, values[1])]
+        );
+
+        let trivalue_cell = cell_with_candidates(3);
+        let value = solver.candidates(trivalue_cell).values()[0];
+        let mut solution = SolutionRecorder::new();
+        solution.add_elimination(
+            Technique::LockedCandidates,
+            String::new(),
+            trivalue_cell,
+            value,
+        );
+        assert!(solution.newly_forced_singles(&solver).is_empty());
+    }
+
+    #[test]
+    fn test_as_dot() {
+        let solver = load_solver(PUZZLE);
+        let dot = solver.as_dot();
+        assert!(dot.starts_with("digraph chain {\n"));
+        assert!(dot.ends_with("}\n"));
+        // r1c3 has candidates 1,2,4
+        let node_id = |label: &str| {
+            let line = dot
+                .lines()
+                .find(|line| line.ends_with(&format!("[label=\"{}\"];", label)))
+                .unwrap();
+            line.trim().split(' ').next().unwrap().to_string()
+        };
+        let on = node_id("r1c3=4");
+        let off = node_id("r1c3<>1");
+        assert!(dot
+            .lines()
+            .any(|line| line.trim() == format!("{} -> {};", on, off)));
+        assert!(!dot.contains("r1c1="));
+    }
+
+    #[test]
+    fn test_candidate_histogram() {
+        let solver = load_solver(PUZZLE);
+        let histogram = solver.candidate_histogram();
+        assert_eq!(histogram.iter().sum::<usize>(), 81);
+        assert_eq!(histogram[0], 30);
+        assert_eq!(histogram[1], 4);
+
+        let solver = load_solver(SOLUTION);
+        assert_eq!(
+            solver.candidate_histogram(),
+            [81, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_assert_solves_with() {
+        let singles = ["FullHouse", "NakedSingle", "HiddenSingle"];
+        assert_eq!(assert_solves_with(PUZZLE, &singles), Ok(()));
+
+        let err = assert_solves_with(PUZZLE, &["FullHouse"]).unwrap_err();
+        assert!(err.starts_with("no step found with 51 unfilled cells after 0 steps\n"));
+        assert!(assert_solves_with(&PUZZLE[1..], &singles).is_err());
+    }
+
+    #[test]
+    fn test_techniques_with_and_without() {
+        let mut solver = SudokuSolver::new(Sudoku::from_values(
+            "8..6.27.........3.6......58...1.....95...8....619..4.......3..47.3....9.529.1....",
+        ));
+        solver.initialize_candidates();
+        let techniques = Techniques::new().without(Technique::Guess);
+        assert!(solver.is_solved_by_logic(&techniques));
+
+        let without_chain = techniques.without(Technique::ForcedChain);
+        assert_eq!(
+            without_chain.techniques.len(),
+            techniques.techniques.len() - 1
+        );
+        assert!(!solver.is_solved_by_logic(&without_chain));
+
+        let with_chain = without_chain.with(Technique::ForcedChain);
+        assert_eq!(with_chain.techniques.last(), Some(&Technique::ForcedChain));
+        assert!(solver.is_solved_by_logic(&with_chain));
+        assert_eq!(
+            with_chain.with(Technique::ForcedChain).techniques.len(),
+            with_chain.techniques.len()
+        );
+    }
+
+    #[test]
+    fn test_custom_techniques() {
+        let techniques =
+            Techniques::from(["full_house", "naked_single", "hidden_single"].into_iter())
+                .with(Technique::LockedCandidates)
+                .without(Technique::HiddenSingle)
+                .with(Technique::HiddenSubset)
+                .with(Technique::NakedSubset);
+        assert!(techniques.contains(Technique::LockedCandidates));
+        assert!(!techniques.contains(Technique::HiddenSingle));
+        assert_eq!(
+            techniques.techniques,
+            vec![
+                Technique::FullHouse,
+                Technique::NakedSingle,
+                Technique::LockedCandidates,
+                Technique::HiddenSubset,
+                Technique::NakedSubset,
+            ]
+        );
+
+        let mut solver = load_solver(PUZZLE);
+        let steps = solver.solve(&techniques).unwrap();
+        assert!(solver.is_completed());
+        assert!(steps
+            .iter()
+            .flat_map(|step| step.steps.iter())
+            .all(|step| step.technique != Technique::HiddenSingle));
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut solver = load_solver(PUZZLE);
+        let steps = solver.solve(&Techniques::new()).unwrap();
+        assert!(solver.is_completed());
+        assert_eq!(solver.sudoku().to_value_string(), SOLUTION);
+        let filled = steps
+            .iter()
+            .flat_map(|step| step.steps.iter())
+            .filter(|step| step.kind == StepKind::ValueSet)
+            .count();
+        assert_eq!(filled, PUZZLE.chars().filter(|&c| c == '.').count());
+    }
+
+    #[test]
+    fn test_is_solvable() {
+        assert!(load_solver(PUZZLE).is_solvable());
+        assert!(!load_solver(&format!("535{}", &PUZZLE[3..])).is_solvable());
+        // r1c3 has candidates 1,2,4 but is 4 in the solution
+        assert!(!load_solver(&format!("531{}", &PUZZLE[3..])).is_solvable());
+        assert!(load_solver(&".".repeat(81)).is_solvable());
+    }
+
+    #[test]
+    fn test_givens() {
+        let mut solver = load_solver(PUZZLE);
+        let givens = CellSet::from_iter(
+            PUZZLE
+                .chars()
+                .positions(|ch| ch != '.')
+                .map(|cell| cell as CellIndex),
+        );
+        assert_eq!(solver.givens(), &givens);
+        solver.solve_to_end(&Techniques::new());
+        assert!(solver.is_completed());
+        assert_eq!(solver.filled_cells().size(), 81);
+        assert_eq!(solver.givens(), &givens);
+        // a new solver reads the givens from the sudoku instead of its filled cells
+        let rebuilt = SudokuSolver::new(solver.take_sudoku());
+        assert_eq!(rebuilt.givens(), &givens);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(load_solver(PUZZLE).validate(), Ok(()));
+
+        let solver = load_solver(&format!("535{}", &PUZZLE[3..]));
+        let conflicts = solver.validate().unwrap_err();
+        assert_eq!(
+            conflicts,
+            vec![
+                Conflict {
+                    kind: ConflictKind::DuplicateValue {
+                        house_name: "b1".to_string(),
+                        value: 5,
+                    },
+                    cells: vec![0, 2],
+                },
+                Conflict {
+                    kind: ConflictKind::DuplicateValue {
+                        house_name: "r1".to_string(),
+                        value: 5,
+                    },
+                    cells: vec![0, 2],
+                },
+            ]
+        );
+        assert_eq!(conflicts[1].to_string(), "r1 has 2 5s");
+        assert_eq!(solver.get_invalid_positions(), vec![0, 2, 0, 2]);
+
+        let mut solver = load_solver(PUZZLE);
+        let mut solution = SolutionRecorder::new();
+        // r1c3 has candidates 1,2,4
+        for value in [1, 2, 4] {
+            solution.add_elimination(Technique::Guess, String::new(), 2, value);
+        }
+        solver.apply_step(&solution);
+        let conflicts = solver.validate().unwrap_err();
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                kind: ConflictKind::NoCandidates,
+                cells: vec![2],
+            }]
+        );
+        assert_eq!(conflicts[0].to_string(), "r1c3 has no candidate");
+    }
+
+    #[test]
+    fn test_hint() {
+        let solver = load_solver(PUZZLE);
+        let techniques = Techniques::from(["full_house", "hidden_single"].into_iter());
+        assert_eq!(
+            solver.hint(&techniques),
+            Some(Hint {
+                technique: Technique::HiddenSingle,
+                technique_name: "HiddenSingle".to_string(),
+                kind: StepKind::ValueSet,
+                reason: "in b2, r1c6 is the only possible cell that can be 8".to_string(),
+                candidates: vec![(5, 8)],
+            })
+        );
+        let no_techniques = Techniques::from(std::iter::empty::<Technique>());
+        assert!(solver.hint(&no_techniques).is_none());
+    }
+
+    #[test]
+    fn test_rate() {
+        const FISH_PUZZLE: &str =
+            "...2.8.6..4.3..9....5.7......49.3...6....2...81......74.......8..6..14....9....3.";
+        let mut solver = load_solver(PUZZLE);
+        let techniques =
+            Techniques::from(["full_house", "hidden_single", "naked_single"].into_iter());
+        let rating = solver.rate(&techniques).unwrap();
+        assert_eq!(rating.hardest_technique, Technique::HiddenSingle);
+        assert_eq!(rating.tier(), DifficultyTier::Easy);
+        assert!(!rating.needs_guessing);
+        assert!(solver.rate(&techniques).is_none());
+
+        let mut solver = load_solver(FISH_PUZZLE);
+        let techniques = Techniques::from(
+            [
+                "full_house",
+                "naked_single",
+                "hidden_single",
+                "locked_candidates",
+                "naked_subset",
+                "hidden_subset",
+                "x_wing",
+                "swordfish",
+            ]
+            .into_iter(),
+        );
+        let fish_rating = solver.rate(&techniques).unwrap();
+        assert_eq!(fish_rating.hardest_technique, Technique::Swordfish);
+        assert_eq!(fish_rating.tier(), DifficultyTier::Hard);
+        assert!(fish_rating.hardest_technique.weight() > rating.hardest_technique.weight());
+        assert!(fish_rating.score > rating.score);
+        assert!(!fish_rating.needs_guessing);
+
+        let mut solver = load_solver(FISH_PUZZLE);
+        let techniques = Techniques::from([Technique::NakedSingle, Technique::Guess].into_iter());
+        let rating = solver.rate(&techniques).unwrap();
+        assert_eq!(rating.hardest_technique, Technique::Guess);
+        assert_eq!(rating.tier(), DifficultyTier::Extreme);
+        assert!(rating.needs_guessing);
+    }
+
+    #[test]
+    fn test_required_guesses() {
+        let techniques = Techniques::from(
+            [
+                Technique::FullHouse,
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+            ]
+            .into_iter(),
+        );
+        let solver = load_solver(PUZZLE);
+        assert!(solver.required_guesses(&techniques).is_empty());
+
+        let solver = load_solver(
+            "4.2..36..8....7..3....4.5.8...36.......4.98..6.9..2......1....5.5.2.476.37.....8.",
+        );
+        // Singles stall on this grid, and guessing r1c2 is enough to finish it
+        assert_eq!(solver.required_guesses(&techniques), vec![(1, 9)]);
+    }
+
+    #[test]
+    fn test_technique_applicable() {
+        let solver = load_solver(&".".repeat(81));
+        assert!(!solver.technique_applicable(Technique::FullHouse));
+        assert!(!solver.technique_applicable(Technique::NakedSingle));
+        assert!(!solver.technique_applicable(Technique::WWing));
+        assert!(!solver.technique_applicable(Technique::XYWing));
+        assert!(!solver.technique_applicable(Technique::XYZWing));
+        assert!(solver.technique_applicable(Technique::HiddenSubset));
+        assert!(solver.technique_applicable(Technique::Guess));
+
+        let solver = load_solver(PUZZLE);
+        assert!(solver.technique_applicable(Technique::NakedSingle));
+        assert!(solver.technique_applicable(Technique::XYWing));
+
+        let solver = load_solver(SOLUTION);
+        assert!(!solver.technique_applicable(Technique::Guess));
+    }
+
+    #[test]
+    fn test_initialize_candidates_preserves_candidates() {
+        let mut solver = load_solver(PUZZLE);
+        let mut step = SolutionRecorder::new();
+        step.add_elimination(Technique::Guess, "manual edit".to_string(), 2, 1);
+        solver.apply_step(&step);
+        let candidates = solver.sudoku().to_candidate_string();
+
+        let mut solver = SudokuSolver::new(Sudoku::from_candidates(&candidates));
+        solver.initialize_candidates();
+        assert_eq!(solver.sudoku().to_candidate_string(), candidates);
+        assert!(!solver.candidates(2).has(1));
+    }
+
+    #[test]
+    fn test_initialize_candidates_fills_empty_cells() {
+        let mut solver = load_solver(PUZZLE);
+        let mut step = SolutionRecorder::new();
+        step.add_elimination(Technique::Guess, "manual edit".to_string(), 2, 1);
+        solver.apply_step(&step);
+        let state = solver.export_state();
+
+        // r1c4 has no candidates, while the other unfilled cells keep theirs
+        let partial = state
+            .split(',')
+            .enumerate()
+            .map(|(idx, cell)| if idx == 3 { "." } else { cell })
+            .join(",");
+        solver.import_state(&partial).unwrap();
+        assert!(solver.candidates(3).is_empty());
+        solver.initialize_candidates();
+        assert_eq!(solver.export_state(), state);
+        assert!(!solver.candidates(2).has(1));
+    }
+
+    #[test]
+    fn test_skip_served_steps() {
+        let mut solver = load_solver(PUZZLE);
+        let techniques = Techniques::new();
+        let first = solver.solve_one_step(&techniques).unwrap();
+        let second = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            first.to_string(solver.sudoku()),
+            second.to_string(solver.sudoku())
+        );
+
+        solver.set_skip_served_steps(true);
+        let first = solver.solve_one_step(&techniques).unwrap();
+        let second = solver.solve_one_step(&techniques).unwrap();
+        let third = solver.solve_one_step(&techniques).unwrap();
+        assert_ne!(
+            first.to_string(solver.sudoku()),
+            second.to_string(solver.sudoku())
+        );
+        assert_ne!(
+            second.to_string(solver.sudoku()),
+            third.to_string(solver.sudoku())
+        );
+        // a served deduction is skipped as a whole, not only its served eliminations
+        let deductions = |solution: &SolutionRecorder| {
+            solution
+                .steps
+                .iter()
+                .map(|step| (step.technique.clone(), step.reason.clone()))
+                .collect::<FxHashSet<_>>()
+        };
+        assert!(deductions(&first).is_disjoint(&deductions(&second)));
+        assert!(deductions(&second).is_disjoint(&deductions(&third)));
+
+        solver.reset_served_steps();
+        let again = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            first.to_string(solver.sudoku()),
+            again.to_string(solver.sudoku())
+        );
+
+        solver.apply_step(&first);
+        let next = solver.solve_one_step(&techniques).unwrap();
+        assert_ne!(
+            first.to_string(solver.sudoku()),
+            next.to_string(solver.sudoku())
+        );
+    }
+
+    #[test]
+    fn test_incremental_caches() {
+        let sudoku = Sudoku::from_candidates(
+            "
+            +----------+----------------+---------+
+            |   9  2 7 |   16   16    3 |  5 4  8 |
+            |   5  3 8 |    9    7    4 |  1 2  6 |
+            |   6  1 4 |   58    2   58 |  9 7  3 |
+            +----------+----------------+---------+
+            |   2 78 1 | 5678 4568 5678 |  3 9 47 |
+            |  47  9 3 |    2   14   17 |  6 8  5 |
+            | 478  6 5 |   78    3    9 |  2 1 47 |
+            +----------+----------------+---------+
+            |   1  4 2 |    3   58  578 | 78 6  9 |
+            |   3 78 9 | 1678  168 1678 |  4 5  2 |
+            |  78  5 6 |    4    9    2 | 78 3  1 |
+            +----------+----------------+---------+
+            ",
+        );
+        let techniques = Techniques::new().without(Technique::Guess);
+        let mut solver = SudokuSolver::new(sudoku);
+        let mut steps = 0;
+        while !solver.is_completed() {
+            // the caches of the incrementally updated solver must match the ones of a fresh solver
+            let fresh = SudokuSolver::new(solver.take_sudoku());
+            for value in 1..=9 {
+                assert_eq!(
+                    solver.candidate_cells_in_rows(value),
+                    fresh.candidate_cells_in_rows(value)
+                );
+                assert_eq!(
+                    solver.candidate_cells_in_columns(value),
+                    fresh.candidate_cells_in_columns(value)
+                );
+                assert_eq!(
+                    solver.candidate_cells_in_blocks(value),
+                    fresh.candidate_cells_in_blocks(value)
+                );
+            }
+            let step = solver.solve_one_step(&techniques).unwrap();
+            let expected = fresh.solve_one_step(&techniques).unwrap();
+            assert_eq!(
+                step.to_string(solver.sudoku()),
+                expected.to_string(fresh.sudoku())
+            );
+            solver.apply_step(&step);
+            steps += 1;
         }
-        Self(funcs)
+        assert!(steps > 1);
     }
 }
@@ -0,0 +1,223 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::CellValue;
+use crate::utils::{CellSet, NamedCellSet, ValueSet};
+
+use itertools::Itertools;
+use rustc_hash::FxHashSet;
+
+/// An Almost Locked Set: `cells` in a single house contain exactly one more candidate than the
+/// number of cells. The name of `cells` is the name of the house.
+#[derive(Debug, Clone)]
+pub struct Als {
+    pub cells: NamedCellSet,
+    pub values: ValueSet,
+}
+
+impl Als {
+    /// The cells of the ALS that can be `value`.
+    pub fn cells_with(&self, sudoku: &SudokuSolver, value: CellValue) -> CellSet {
+        &*self.cells & sudoku.possible_cells(value)
+    }
+
+    pub fn to_string(&self, sudoku: &SudokuSolver) -> String {
+        format!(
+            "{}({}) in {}",
+            sudoku.get_cellset_string(&self.cells),
+            self.values.iter().join(""),
+            self.cells.name(),
+        )
+    }
+}
+
+/// Enumerates the ALSes of at most `max_size` cells in every house. A set of cells lying in more
+/// than one house, e.g. in a row and a block, is only reported once under the first house in
+/// `all_constraints`. A bivalue cell is an ALS of size 1.
+pub fn find_als(sudoku: &SudokuSolver, max_size: usize) -> Vec<Als> {
+    let mut seen = FxHashSet::default();
+    let mut result = vec![];
+    for house in sudoku.all_constraints().iter() {
+        let unfilled = (&**house & sudoku.unfilled_cells()).values().to_vec();
+        // 包含 House 中所有未填单元格的集合有 n 个数字，不可能是 ALS
+        for size in 1..=max_size.min(unfilled.len().saturating_sub(1)) {
+            for cells in unfilled.iter().copied().combinations(size) {
+                let values =
+                    ValueSet::union_multiple(cells.iter().map(|&cell| sudoku.candidates(cell)));
+                if values.size() != size + 1 {
+                    continue;
+                }
+                if !seen.insert(cells.clone()) {
+                    continue;
+                }
+                result.push(Als {
+                    cells: NamedCellSet::from_cellset(house, CellSet::from_iter(cells)),
+                    values,
+                });
+            }
+        }
+    }
+    result
+}
+
+/// Whether every cell of `als1` and `als2` that can be `value` sees all the others, i.e. `value`
+/// can be true in at most one of the two ALSes.
+pub fn is_restricted_common(
+    sudoku: &SudokuSolver,
+    als1: &Als,
+    als2: &Als,
+    value: CellValue,
+) -> bool {
+    let cells1 = als1.cells_with(sudoku, value);
+    let cells2 = als2.cells_with(sudoku, value);
+    if cells1.is_empty() || cells2.is_empty() || !(&cells1 & &cells2).is_empty() {
+        return false;
+    }
+    cells1
+        .iter()
+        .all(|cell| cells2.is_subset_of(sudoku.house_union_of_cell(cell)))
+}
+
+// ALS-XZ：两个不相交的 ALS A 与 B 有公共数字 X 与 Z，且 A 与 B 中所有 X 所在的单元格互相可见（restricted common）。
+// X 至多在其中一个 ALS 中为真，另一个 ALS 去掉 X 后成为 locked set，因此 Z 必在 A 或 B 中。
+// 能看到 A 与 B 中所有 Z 所在单元格的单元格中不可能出现 Z。
+pub fn solve_als_xz(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let all_als = find_als(sudoku, 8);
+    // 只组合至少有两个公共数字的 ALS，按数字建立索引以避免检查所有的 ALS 对
+    let mut als_with_value = vec![vec![]; 10];
+    for (idx, als) in all_als.iter().enumerate() {
+        for value in als.values.iter() {
+            als_with_value[value as usize].push(idx);
+        }
+    }
+    let mut common_counts = vec![0; all_als.len()];
+    for (idx1, als1) in all_als.iter().enumerate() {
+        let mut partners = vec![];
+        for value in als1.values.iter() {
+            let indices = &als_with_value[value as usize];
+            for &idx2 in &indices[indices.partition_point(|&idx| idx <= idx1)..] {
+                common_counts[idx2] += 1;
+                if common_counts[idx2] == 2 {
+                    partners.push(idx2);
+                }
+            }
+        }
+        for value in als1.values.iter() {
+            for &idx2 in als_with_value[value as usize].iter() {
+                common_counts[idx2] = 0;
+            }
+        }
+        partners.sort_unstable();
+        for idx2 in partners {
+            solve_als_xz_pair(sudoku, solution, als1, &all_als[idx2]);
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+fn solve_als_xz_pair(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    als1: &Als,
+    als2: &Als,
+) {
+    if !(&*als1.cells & &*als2.cells).is_empty() {
+        return;
+    }
+    let common = &als1.values & &als2.values;
+    for x in common.iter() {
+        if !is_restricted_common(sudoku, als1, als2, x) {
+            continue;
+        }
+        for z in common.iter().filter(|&z| z != x) {
+            let z_cells = &als1.cells_with(sudoku, z) | &als2.cells_with(sudoku, z);
+            let mut eliminated = sudoku.possible_cells(z).clone();
+            for cell in z_cells.iter() {
+                eliminated &= sudoku.house_union_of_cell(cell);
+            }
+            eliminated -= &z_cells;
+            for cell in eliminated.iter() {
+                solution.add_elimination(
+                    Technique::AlsXz,
+                    format!(
+                        "the ALSes {} and {} share the restricted common {}, so one of them contains {}",
+                        als1.to_string(sudoku),
+                        als2.to_string(sudoku),
+                        x,
+                        z,
+                    ),
+                    cell,
+                    z,
+                );
+            }
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::StepKind;
+    use crate::sudoku::Sudoku;
+
+    const ALS_XZ_GRID: &str = "
+        +---------+--------------+-----------+
+        |  8 19 5 |   6    3   2 |  7  4  19 |
+        |  4 19 7 |  58   58  19 | 26  3  26 |
+        |  6  3 2 |  47   47  19 | 19  5   8 |
+        +---------+--------------+-----------+
+        | 23  7 8 |   1 2456 456 | 39 26 359 |
+        |  9  5 4 |   3   26   8 | 26  1   7 |
+        | 23  6 1 |   9  257  57 |  4  8  35 |
+        +---------+--------------+-----------+
+        |  1  8 6 |  27    9   3 |  5 27   4 |
+        |  7  4 3 | 258  568  56 | 18  9 126 |
+        |  5  2 9 | 478    1 467 | 38 67  36 |
+        +---------+--------------+-----------+
+    ";
+
+    #[test]
+    fn test_find_als() {
+        let solver = SudokuSolver::new(Sudoku::from_candidates(ALS_XZ_GRID));
+        let all_als = find_als(&solver, 8);
+        assert!(all_als
+            .iter()
+            .all(|als| als.values.size() == als.cells.size() + 1));
+        let names = all_als
+            .iter()
+            .map(|als| solver.get_cellset_string(&als.cells))
+            .collect_vec();
+        assert_eq!(names.iter().unique().count(), names.len());
+        // a bivalue cell
+        assert!(names.contains(&"r6c6".to_string()));
+        // r4c1 and r4c8 contain 2, 3 and 6
+        assert!(names.contains(&"r4c1,r4c8".to_string()));
+        // r4c1 and r6c1 only contain 2 and 3, they are a locked set
+        assert!(!names.contains(&"r4c1,r6c1".to_string()));
+
+        let small_als = find_als(&solver, 1);
+        assert!(small_als.iter().all(|als| als.cells.size() == 1));
+        assert_eq!(
+            small_als.len(),
+            solver
+                .unfilled_cells()
+                .iter()
+                .filter(|&cell| solver.candidates(cell).size() == 2)
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_als_xz() {
+        let solver = SudokuSolver::new(Sudoku::from_candidates(ALS_XZ_GRID));
+        let mut solution = SolutionRecorder::new();
+        solve_als_xz(&solver, &mut solution);
+        assert_eq!(solution.steps.len(), 1);
+        let step = &solution.steps[0];
+        assert_eq!(step.kind, StepKind::CandidateEliminated);
+        assert_eq!((step.cell_index, step.value), (31, 5));
+        assert_eq!(
+            step.reason,
+            "the ALSes r6c6(57) in b5 and r2c5,r5c5,r6c5,r8c5(25678) in c5 share the restricted common 7, so one of them contains 5"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::solver::{SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+
+// BUG+1（Bivalue Universal Grave）：若所有未填的单元格都只有两个候选数，且每个数字在每个 House 中都恰好出现两次，
+// 则可以交替选择每个单元格中的两个数字得到两个解（deadly pattern）。假设数独有唯一解，则必须避免这种情况。
+// 若只有一个单元格有三个候选数，且其中一个数字 x 在该单元格所在的三个 House 中出现三次，其余数字都出现两次，
+// 则删除 x 后即为 BUG，因此该单元格必为 x。
+pub fn solve_bug(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let mut trivalue_cell = None;
+    for cell in sudoku.unfilled_cells().iter() {
+        match sudoku.candidates(cell).size() {
+            2 => {}
+            3 if trivalue_cell.is_none() => trivalue_cell = Some(cell),
+            _ => return,
+        }
+    }
+    let Some(trivalue_cell) = trivalue_cell else {
+        return;
+    };
+
+    let mut extra_value = None;
+    for value in sudoku.candidates(trivalue_cell).iter() {
+        if is_extra_value(sudoku, trivalue_cell, value) {
+            if extra_value.is_some() {
+                return;
+            }
+            extra_value = Some(value);
+        }
+    }
+    let Some(extra_value) = extra_value else {
+        return;
+    };
+
+    for house in sudoku.all_constraints().iter() {
+        for value in 1..=9 {
+            let count = sudoku
+                .get_possible_cells_for_house_and_value(house, value)
+                .size();
+            let expected = if value == extra_value && house.has(trivalue_cell) {
+                3
+            } else {
+                2
+            };
+            if count != 0 && count != expected {
+                return;
+            }
+        }
+    }
+
+    solution.add_value_set(
+        Technique::BUG,
+        format!(
+            "all unfilled cells except {} are bivalue, removing {} from {} would leave a bivalue universal grave with two solutions",
+            sudoku.get_cell_name(trivalue_cell),
+            extra_value,
+            sudoku.get_cell_name(trivalue_cell),
+        ),
+        trivalue_cell,
+        extra_value,
+    );
+}
+
+fn is_extra_value(sudoku: &SudokuSolver, cell: CellIndex, value: CellValue) -> bool {
+    sudoku
+        .all_constraints()
+        .iter()
+        .filter(|house| house.has(cell))
+        .all(|house| {
+            sudoku
+                .get_possible_cells_for_house_and_value(house, value)
+                .size()
+                == 3
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Techniques;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_bug() {
+        let techniques = Techniques::from(["bug"].into_iter());
+        let sudoku = Sudoku::from_candidates(
+            "
+            25 8 25 7 9 1 4 6 3
+            4 67 67 8 5 3 2 1 9
+            9 3 1 6 2 4 5 8 7
+            36 69 369 4 1 5 7 2 8
+            27 27 4 3 8 6 1 9 5
+            1 5 8 2 7 9 6 3 4
+            56 4 56 1 3 8 9 7 2
+            37 1 37 9 4 2 8 5 6
+            8 29 29 5 6 7 3 4 1
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            solution.to_string(solver.sudoku()).trim(),
+            "[BUG] all unfilled cells except r4c3 are bivalue, removing 6 from r4c3 would leave a bivalue universal grave with two solutions => r4c3=6"
+        );
+
+        let mut solver = SudokuSolver::new(Sudoku::from_values(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        ));
+        solver.initialize_candidates();
+        assert!(solver.solve_one_step(&techniques).is_none());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::solver::chain::forced_chain::build_implication_graph;
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+
+// 两端都为 on 的 AIC：若 A 为假可以推出 B 为真（A 与 B 至少一个为真），且 A 为真也可以推出 B 为真，
+// 则无论 A 是否为真，B 都为真，可以直接填入 B。
+// 当 A 与 B 为同一候选数时，即为假设 B 为假推出 B 为真的不连续环。
+pub fn solve_aic_type2(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let (graph, on_assumptions, off_assumptions) = build_implication_graph(sudoku);
+    let candidates = sudoku
+        .unfilled_cells()
+        .iter()
+        .flat_map(|cell| {
+            sudoku
+                .candidates(cell)
+                .iter()
+                .map(move |value| (cell, value))
+        })
+        .collect::<Vec<_>>();
+
+    for &(cell, value) in candidates.iter() {
+        let on = on_assumptions[cell as usize][value as usize - 1].unwrap();
+        let off = off_assumptions[cell as usize][value as usize - 1].unwrap();
+        if graph.get_edge(off, on).is_some() {
+            solution.add_value_set(
+                Technique::AicType2,
+                format!(
+                    "aic from {}<>{} to {}={}\n{}",
+                    sudoku.get_cell_name(cell),
+                    value,
+                    sudoku.get_cell_name(cell),
+                    value,
+                    graph.path_to_string(sudoku, off, on),
+                ),
+                cell,
+                value,
+            );
+            return_in_fast_mode!(solution);
+            continue;
+        }
+
+        for &(other_cell, other_value) in candidates.iter() {
+            if (other_cell, other_value) == (cell, value) {
+                continue;
+            }
+            let other_on = on_assumptions[other_cell as usize][other_value as usize - 1].unwrap();
+            let other_off = off_assumptions[other_cell as usize][other_value as usize - 1].unwrap();
+            if graph.get_edge(other_on, on).is_none() || graph.get_edge(other_off, on).is_none() {
+                continue;
+            }
+            solution.add_value_set(
+                Technique::AicType2,
+                format!(
+                    "aic with both ends on, {} is {} whether {} is {} or not\n{}\n{}",
+                    sudoku.get_cell_name(cell),
+                    value,
+                    sudoku.get_cell_name(other_cell),
+                    other_value,
+                    graph.path_to_string(sudoku, other_on, on),
+                    graph.path_to_string(sudoku, other_off, on),
+                ),
+                cell,
+                value,
+            );
+            return_in_fast_mode!(solution);
+            break;
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::Write;
 
-use crate::solver::{SolutionRecorder, SudokuSolver, Technique};
+use crate::solver::{CellHighlight, HighlightRole, SolutionRecorder, SudokuSolver, Technique};
 use crate::sudoku::{CellIndex, CellValue};
 
 use itertools::Itertools;
@@ -13,12 +13,58 @@ pub struct Assumption {
     added_to_solution: bool,
 }
 
+impl Assumption {
+    pub fn cell(&self) -> CellIndex {
+        self.cell
+    }
+
+    pub fn value(&self) -> CellValue {
+        self.value
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.kind == AssumptionKind::On
+    }
+
+    /// `r1c1=1` for an "on" assumption and `r1c1<>1` for an "off" assumption.
+    pub fn name(&self, sudoku: &SudokuSolver) -> String {
+        let relation = match self.kind {
+            AssumptionKind::On => "=",
+            AssumptionKind::Off => "<>",
+        };
+        format!(
+            "{}{}{}",
+            sudoku.get_cell_name(self.cell),
+            relation,
+            self.value
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AssumptionKind {
     On,
     Off,
 }
 
+/// The kind of a direct implication between two assumptions, in the sense of AIC notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// At least one end is true: turning the start off makes the end on.
+    Strong,
+    /// At most one end is true: turning the start on makes the end off.
+    Weak,
+}
+
+impl LinkKind {
+    fn symbol(&self) -> &'static str {
+        match self {
+            LinkKind::Strong => "=",
+            LinkKind::Weak => "-",
+        }
+    }
+}
+
 type EdgeId = u32;
 
 #[derive(Debug)]
@@ -30,6 +76,10 @@ pub struct Edge {
     /// If the edge is a chain, the start_middle node is the next node of the start node and the end node is the next node of the middle_end node.
     start_middle: Option<NodeId>,
     middle_end: Option<NodeId>,
+    /// The kind of a direct edge. It is `None` for the edges added by expanding chains.
+    link: Option<LinkKind>,
+    /// The number of nodes between the start node and the end node, 0 for a direct edge.
+    middle_count: usize,
 }
 
 // save the graph as chain foward star
@@ -41,7 +91,7 @@ pub struct Graph {
     edge_set: FxHashMap<(NodeId, NodeId), EdgeId>,
 }
 
-type NodeId = u32;
+pub type NodeId = u32;
 
 impl Graph {
     pub fn new() -> Self {
@@ -74,8 +124,8 @@ impl Graph {
         idx as NodeId
     }
 
-    pub fn add_edge(&mut self, start: NodeId, end: NodeId) {
-        self.add_big_edge(start, end, None, None)
+    pub fn add_edge(&mut self, start: NodeId, end: NodeId, link: LinkKind) {
+        self.insert_edge(start, end, None, None, Some(link), 0)
     }
 
     pub fn add_big_edge(
@@ -84,6 +134,19 @@ impl Graph {
         end: NodeId,
         start_middle: Option<NodeId>,
         middle_end: Option<NodeId>,
+        middle_count: usize,
+    ) {
+        self.insert_edge(start, end, start_middle, middle_end, None, middle_count)
+    }
+
+    fn insert_edge(
+        &mut self,
+        start: NodeId,
+        end: NodeId,
+        start_middle: Option<NodeId>,
+        middle_end: Option<NodeId>,
+        link: Option<LinkKind>,
+        middle_count: usize,
     ) {
         debug_assert_ne!(start, end);
         if self.edge_set.contains_key(&(start, end)) {
@@ -101,6 +164,8 @@ impl Graph {
             end,
             start_middle,
             middle_end,
+            link,
+            middle_count,
             next: old_head,
             rev_next: old_rev_head,
         });
@@ -112,43 +177,91 @@ impl Graph {
             .map(|&idx| &self.edges[idx as usize])
     }
 
-    pub fn path_to_string(&self, sudoku: &SudokuSolver, start: NodeId, end: NodeId) -> String {
-        let write_path = |path: &mut dyn Write, assumption: &Assumption, trailing_space: bool| {
-            if assumption.kind == AssumptionKind::On {
-                write!(
-                    path,
-                    "{}={}",
-                    sudoku.get_cell_name(assumption.cell),
-                    assumption.value
-                )
-                .unwrap();
-            } else {
-                write!(
-                    path,
-                    "{}<>{}",
-                    sudoku.get_cell_name(assumption.cell),
-                    assumption.value
-                )
-                .unwrap();
-            }
-            if trailing_space {
-                write!(path, " ").unwrap();
-            }
-        };
+    /// The direct weak links, as pairs of an "on" node and the "off" node it implies.
+    pub fn weak_links(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.edges
+            .iter()
+            .filter(|edge| edge.link == Some(LinkKind::Weak))
+            .map(|edge| (edge.start, edge.end))
+    }
 
-        let mut path = String::new();
+    /// The nodes on the chain from `start` to `end`, including both ends.
+    pub fn path_nodes(&self, start: NodeId, end: NodeId) -> Vec<NodeId> {
+        let mut nodes = vec![];
         let mut edge = self.get_edge(start, end).unwrap();
-        while edge.start_middle.is_some() {
-            write_path(&mut path, self.get_node(edge.start), true);
-            edge = self.get_edge(edge.start_middle.unwrap(), end).unwrap();
+        while let Some(start_middle) = edge.start_middle {
+            nodes.push(edge.start);
+            edge = self.get_edge(start_middle, end).unwrap();
+        }
+        nodes.push(edge.start);
+        nodes.push(edge.end);
+        nodes
+    }
+
+    /// The chain from `start` to `end` in AIC notation, e.g. `r1c1<>1 = r1c1=2 - r1c2<>2`, where
+    /// `=` is a strong link and `-` is a weak link.
+    pub fn path_to_string(&self, sudoku: &SudokuSolver, start: NodeId, end: NodeId) -> String {
+        let nodes = self.path_nodes(start, end);
+        let mut path = String::new();
+        for (&node, &next) in nodes.iter().tuple_windows() {
+            let link = self.get_edge(node, next).unwrap().link.unwrap();
+            write!(
+                path,
+                "{} {} ",
+                self.get_node(node).name(sudoku),
+                link.symbol()
+            )
+            .unwrap();
         }
-        write_path(&mut path, self.get_node(edge.start), true);
-        write_path(&mut path, self.get_node(edge.end), false);
+        write!(path, "{}", self.get_node(end).name(sudoku)).unwrap();
         path
     }
+
+    /// The candidates of the nodes on the chain from `start` to `end`.
+    pub fn path_highlights(&self, start: NodeId, end: NodeId) -> Vec<CellHighlight> {
+        self.path_nodes(start, end)
+            .into_iter()
+            .map(|node| CellHighlight {
+                cell: self.get_node(node).cell,
+                value: self.get_node(node).value,
+                role: HighlightRole::ChainLink,
+            })
+            .unique()
+            .collect()
+    }
+
+    /// Exports the graph in Graphviz DOT format. Only the direct implications are exported unless
+    /// `with_chain_edges` is set, since the edges added by expanding chains make the graph unreadable.
+    pub fn to_dot(&self, sudoku: &SudokuSolver, with_chain_edges: bool) -> String {
+        let mut dot = String::from("digraph chain {\n");
+        for (idx, node) in self.nodes.iter().enumerate() {
+            writeln!(dot, "    {} [label=\"{}\"];", idx, node.name(sudoku)).unwrap();
+        }
+        for edge in self.edges.iter() {
+            if with_chain_edges || edge.link.is_some() {
+                writeln!(dot, "    {} -> {};", edge.start, edge.end).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
 }
 
-pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+/// The id of the "on" or "off" node of each candidate, indexed by cell and value - 1.
+pub type AssumptionIds = [[Option<NodeId>; 9]; 81];
+
+/// Builds the implication graph of the candidates, where an edge from one assumption to another means
+/// that the first one implies the second one, directly or through a chain.
+pub fn build_implication_graph(sudoku: &SudokuSolver) -> (Graph, AssumptionIds, AssumptionIds) {
+    build_implication_graph_with_max_length(sudoku, usize::MAX)
+}
+
+/// Same as `build_implication_graph`, but only adds the chains with at most `max_length` nodes
+/// between the two ends, which bounds the number of edges on hard grids.
+pub fn build_implication_graph_with_max_length(
+    sudoku: &SudokuSolver,
+    max_length: usize,
+) -> (Graph, AssumptionIds, AssumptionIds) {
     let mut graph = Graph::new();
 
     let mut on_assumptions = [[None; 9]; 81];
@@ -188,7 +301,7 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                 if i == j {
                     continue;
                 }
-                graph.add_edge(on, off);
+                graph.add_edge(on, off, LinkKind::Weak);
             }
 
             // turning a cell's value on makes the same value in other cells within the same house off
@@ -196,7 +309,7 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                 for off in off_assumptions[other as usize].iter() {
                     if let Some(&off) = off.as_ref() {
                         if graph.get_node(off).value == graph.get_node(on).value {
-                            graph.add_edge(on, off);
+                            graph.add_edge(on, off, LinkKind::Weak);
                         }
                     }
                 }
@@ -212,8 +325,8 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
             let on2 = on_assumptions[cell as usize][value2 as usize - 1].unwrap();
             let off1 = off_assumptions[cell as usize][value1 as usize - 1].unwrap();
             let off2 = off_assumptions[cell as usize][value2 as usize - 1].unwrap();
-            graph.add_edge(off1, on2);
-            graph.add_edge(off2, on1);
+            graph.add_edge(off1, on2, LinkKind::Strong);
+            graph.add_edge(off2, on1, LinkKind::Strong);
         }
     }
 
@@ -229,8 +342,8 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                 let on2 = on_assumptions[cell2 as usize][value as usize - 1].unwrap();
                 let off1 = off_assumptions[cell1 as usize][value as usize - 1].unwrap();
                 let off2 = off_assumptions[cell2 as usize][value as usize - 1].unwrap();
-                graph.add_edge(off1, on2);
-                graph.add_edge(off2, on1);
+                graph.add_edge(off1, on2, LinkKind::Strong);
+                graph.add_edge(off2, on1, LinkKind::Strong);
             }
         }
     }
@@ -243,9 +356,16 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
     // When expanding the graph, we only expend the edges with length 1.
     // This can be done by backing up the heads and rev_heads and iterating through the edges,
     // since the new edges are always added to the front.
+    // The edges are expanded in the order they are added, so the chains are added from the shortest
+    // to the longest, and the expansion can stop at the first chain that is too long.
     let heads = graph.heads.clone();
     let rev_heads = graph.rev_heads.clone();
     while idx < graph.edges.len() {
+        let middle_count = graph.edges[idx].middle_count + 1;
+        if middle_count > max_length {
+            break;
+        }
+
         let u = graph.edges[idx].start;
         let v = graph.edges[idx].end;
 
@@ -260,6 +380,7 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                     w,
                     graph.edges[idx].start_middle.or(Some(v)),
                     v_to_w.middle_end.or(Some(v)),
+                    middle_count,
                 );
             }
         }
@@ -278,6 +399,7 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                     w,
                     u_to_v.start_middle.or(Some(v)),
                     graph.edges[idx].middle_end.or(Some(v)),
+                    middle_count,
                 );
             }
         }
@@ -285,6 +407,23 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
         idx += 1;
     }
 
+    (graph, on_assumptions, off_assumptions)
+}
+
+pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    solve_forced_chain_with_max_length(sudoku, solution, usize::MAX);
+}
+
+/// Same as `solve_forced_chain`, but only considers chains with at most `max_length` nodes between
+/// the two ends. This trades completeness for a predictable memory usage.
+pub fn solve_forced_chain_with_max_length(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    max_length: usize,
+) {
+    let (mut graph, on_assumptions, off_assumptions) =
+        build_implication_graph_with_max_length(sudoku, max_length);
+
     // All the nodes that can reach the contradiction node are also forced to be false, that is, their opposite nodes are forced to be true.
     let check_can_reach_contradiction =
         |solution: &mut SolutionRecorder, graph: &mut Graph, contradiction: NodeId| {
@@ -299,6 +438,7 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                 };
                 let opposite = graph.get_node(opposite_node);
                 if !opposite.added_to_solution {
+                    let len = solution.steps.len();
                     if opposite.kind == AssumptionKind::On {
                         solution.add_value_set(
                             Technique::ForcedChain,
@@ -320,6 +460,8 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                             opposite.value,
                         );
                     }
+                    solution
+                        .highlight_steps_since(len, &graph.path_highlights(edge.start, edge.end));
                     graph.get_node_mut(opposite_node).added_to_solution = true;
                 }
             }
@@ -333,6 +475,7 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
             if let Some(_) = graph.edge_set.get(&(on, off)) {
                 let eliminated_cell = graph.get_node(off).cell;
                 let eliminated_value = graph.get_node(off).value;
+                let len = solution.steps.len();
                 solution.add_elimination(
                     Technique::ForcedChain,
                     format!(
@@ -344,12 +487,14 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                     eliminated_cell,
                     eliminated_value,
                 );
+                solution.highlight_steps_since(len, &graph.path_highlights(on, off));
                 graph.get_node_mut(off).added_to_solution = true;
                 check_can_reach_contradiction(solution, &mut graph, on);
             }
             if let Some(_) = graph.edge_set.get(&(off, on)) {
                 let forced_cell = graph.get_node(on).cell;
                 let forced_value = graph.get_node(on).value;
+                let len = solution.steps.len();
                 solution.add_value_set(
                     Technique::ForcedChain,
                     format!(
@@ -361,6 +506,7 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                     forced_cell,
                     forced_value,
                 );
+                solution.highlight_steps_since(len, &graph.path_highlights(off, on));
                 graph.get_node_mut(on).added_to_solution = true;
                 check_can_reach_contradiction(solution, &mut graph, off);
             }
@@ -395,6 +541,16 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                     graph.path_to_string(sudoku, on, i as NodeId)
                 })
                 .join("\n");
+            let highlights = sudoku
+                .candidates(cell)
+                .iter()
+                .flat_map(|value| {
+                    let on = on_assumptions[cell as usize][value as usize - 1].unwrap();
+                    graph.path_highlights(on, i as NodeId)
+                })
+                .unique()
+                .collect_vec();
+            let len = solution.steps.len();
             if assumption.kind == AssumptionKind::On {
                 solution.add_value_set(
                     Technique::ForcedChain,
@@ -408,7 +564,6 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                     assumption.cell,
                     assumption.value,
                 );
-                graph.nodes[i].added_to_solution = true;
             } else {
                 solution.add_elimination(
                     Technique::ForcedChain,
@@ -422,8 +577,9 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                     assumption.cell,
                     assumption.value,
                 );
-                graph.nodes[i].added_to_solution = true;
             }
+            solution.highlight_steps_since(len, &highlights);
+            graph.nodes[i].added_to_solution = true;
         }
     }
 
@@ -468,6 +624,16 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                         graph.path_to_string(sudoku, on, assumption_idx as NodeId)
                     })
                     .join("\n");
+                let highlights = sudoku
+                    .get_possible_cells_for_house_and_value(house, value)
+                    .iter()
+                    .flat_map(|cell| {
+                        let on = on_assumptions[cell as usize][value as usize - 1].unwrap();
+                        graph.path_highlights(on, assumption_idx as NodeId)
+                    })
+                    .unique()
+                    .collect_vec();
+                let len = solution.steps.len();
                 if assumption.kind == AssumptionKind::On {
                     solution.add_value_set(
                         Technique::ForcedChain,
@@ -482,7 +648,6 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                         assumption.cell,
                         assumption.value,
                     );
-                    graph.nodes[assumption_idx].added_to_solution = true;
                 } else {
                     solution.add_elimination(
                         Technique::ForcedChain,
@@ -497,9 +662,123 @@ pub fn solve_forced_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
                         assumption.cell,
                         assumption.value,
                     );
-                    graph.nodes[assumption_idx].added_to_solution = true;
                 }
+                solution.highlight_steps_since(len, &highlights);
+                graph.nodes[assumption_idx].added_to_solution = true;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::chain::solve_aic_type2;
+    use crate::sudoku::Sudoku;
+
+    const CHAIN_GRID: &str = "
+        358 9 2 7 156 1356 36 18 4
+        35 1 47 345 569 8 79 2 36
+        38 6 47 1234 19 12349 79 18 5
+        27 257 8 9 1567 12567 4 3 126
+        4 257 9 125 3 12567 8 56 126
+        6 3 1 8 4 25 25 7 9
+        1 8 6 345 2 3459 35 459 7
+        279 27 5 134 1789 13479 236 469 2368
+        279 4 3 6 5789 579 1 59 28
+    ";
+
+    #[test]
+    fn test_to_dot() {
+        let sudoku = Sudoku::from_values(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        );
+        let mut solver = SudokuSolver::new(sudoku);
+        solver.initialize_candidates();
+        let (graph, _, _) = build_implication_graph(&solver);
+
+        let dot = graph.to_dot(&solver, false);
+        let lines = dot.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "digraph chain {");
+        assert_eq!(*lines.last().unwrap(), "}");
+        let nodes = lines.iter().filter(|line| line.contains("[label=")).count();
+        let edges = lines.iter().filter(|line| line.contains(" -> ")).count();
+        assert_eq!(nodes, graph.nodes.len());
+        assert_eq!(nodes + edges + 2, lines.len());
+        assert_eq!(
+            edges,
+            graph
+                .edges
+                .iter()
+                .filter(|edge| edge.link.is_some())
+                .count()
+        );
+
+        let full_dot = graph.to_dot(&solver, true);
+        let full_edges = full_dot
+            .lines()
+            .filter(|line| line.contains(" -> "))
+            .count();
+        assert_eq!(full_edges, graph.edges.len());
+        assert!(full_edges > edges);
+    }
+
+    #[test]
+    fn test_max_length() {
+        let solver = SudokuSolver::new(Sudoku::from_candidates(CHAIN_GRID));
+        let (graph, _, _) = build_implication_graph(&solver);
+        let (bounded_graph, _, _) = build_implication_graph_with_max_length(&solver, 2);
+        assert!(bounded_graph.edges.len() * 4 < graph.edges.len());
+        assert!(bounded_graph
+            .edges
+            .iter()
+            .all(|edge| edge.middle_count <= 2));
+
+        let mut solution = SolutionRecorder::new();
+        solve_forced_chain_with_max_length(&solver, &mut solution, 2);
+        assert!(solution.has_new_step());
+        for step in solution.steps.iter() {
+            let path = step.reason.lines().nth(1).unwrap();
+            // the two ends and at most 2 nodes in between, separated by links
+            assert!(path.split(' ').count() <= 7);
+        }
+    }
+
+    #[test]
+    fn test_chain_highlights() {
+        let solver = SudokuSolver::new(Sudoku::from_candidates(CHAIN_GRID));
+        let mut solution = SolutionRecorder::new();
+        solve_forced_chain_with_max_length(&solver, &mut solution, 2);
+        assert!(solution.has_new_step());
+        for step in solution.steps.iter() {
+            assert!(step
+                .highlights
+                .iter()
+                .all(|highlight| highlight.role == HighlightRole::ChainLink));
+            // the deduced candidate is an end of the chain
+            assert!(step.highlights.iter().any(
+                |highlight| (highlight.cell, highlight.value) == (step.cell_index, step.value)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_path_links_alternate() {
+        let sudoku = Sudoku::from_candidates(CHAIN_GRID);
+        let solver = SudokuSolver::new(sudoku);
+        let mut solution = SolutionRecorder::new();
+        solve_aic_type2(&solver, &mut solution);
+        let reason = &solution.steps[0].reason;
+        let path = reason.lines().nth(1).unwrap();
+        assert_eq!(
+            path,
+            "r1c7<>6 = r1c7=3 - r7c7<>3 = r7c7=5 - r6c7<>5 = r5c8=5 - r5c8<>6 = r8c8=6 - r8c7<>6 = r1c7=6"
+        );
+
+        let links = path.split(' ').skip(1).step_by(2).collect::<Vec<_>>();
+        assert_eq!(links.len(), 9);
+        for (i, link) in links.iter().enumerate() {
+            assert_eq!(*link, if i % 2 == 0 { "=" } else { "-" });
+        }
+    }
+}
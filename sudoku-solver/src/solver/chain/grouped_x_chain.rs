@@ -0,0 +1,178 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::CellValue;
+use crate::utils::{CellSet, NamedCellSet};
+
+use std::collections::VecDeque;
+
+use itertools::Itertools;
+
+// 单数字的 grouped AIC：节点为单个单元格，或一个 block 与一行（列）的交集中所有可能的单元格（grouped node）。
+// strong link：一个 House 中该数字的所有可能位置恰好被两个不相交的节点覆盖，则两个节点中至少一个为真。
+// weak link：两个不相交的节点位于同一个 House 中，则两个节点中至多一个为真。
+// 从节点 S 为假开始，沿 strong link 与 weak link 交替推导，若推导出节点 E 为真，则 S 与 E 至少一个为真，
+// 能同时看到 S 与 E 中所有单元格的单元格中不可能出现该数字。
+pub fn solve_grouped_x_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for value in 1..=9 {
+        let graph = LinkGraph::new(sudoku, value);
+        for start in 0..graph.nodes.len() {
+            search_from(sudoku, solution, &graph, value, start);
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+struct LinkGraph {
+    nodes: Vec<CellSet>,
+    strong_links: Vec<Vec<usize>>,
+    weak_links: Vec<Vec<usize>>,
+}
+
+impl LinkGraph {
+    fn new(sudoku: &SudokuSolver, value: CellValue) -> Self {
+        let possible_cells = sudoku.possible_cells(value);
+        let mut nodes: Vec<CellSet> = possible_cells
+            .iter()
+            .map(|cell| CellSet::from_iter([cell]))
+            .collect();
+        for block in sudoku.cells_in_blocks() {
+            for line in sudoku
+                .cells_in_rows()
+                .iter()
+                .chain(sudoku.cells_in_columns())
+            {
+                let group = &(block & line) & possible_cells;
+                if group.size() >= 2 {
+                    nodes.push(group);
+                }
+            }
+        }
+
+        let houses_of_node = |node: &CellSet| -> Vec<&NamedCellSet> {
+            sudoku
+                .all_constraints()
+                .iter()
+                .filter(|house| node.is_subset_of(house))
+                .collect()
+        };
+        let houses = nodes.iter().map(houses_of_node).collect_vec();
+
+        let mut strong_links = vec![vec![]; nodes.len()];
+        let mut weak_links = vec![vec![]; nodes.len()];
+        for (i, j) in (0..nodes.len()).tuple_combinations() {
+            if !(&nodes[i] & &nodes[j]).is_empty() {
+                continue;
+            }
+            let union = &nodes[i] | &nodes[j];
+            let common_houses = houses[i].iter().filter(|house| houses[j].contains(house));
+            let mut is_weak = false;
+            let mut is_strong = false;
+            for house in common_houses {
+                is_weak = true;
+                let cells_in_house: &CellSet =
+                    sudoku.get_possible_cells_for_house_and_value(house, value);
+                if cells_in_house == &union {
+                    is_strong = true;
+                }
+            }
+            if is_weak {
+                weak_links[i].push(j);
+                weak_links[j].push(i);
+            }
+            if is_strong {
+                strong_links[i].push(j);
+                strong_links[j].push(i);
+            }
+        }
+
+        Self {
+            nodes,
+            strong_links,
+            weak_links,
+        }
+    }
+
+    fn node_to_string(&self, sudoku: &SudokuSolver, node: usize) -> String {
+        let cells = &self.nodes[node];
+        if cells.size() == 1 {
+            sudoku.get_cell_name(cells.values()[0])
+        } else {
+            format!("({})", sudoku.get_cellset_string(cells))
+        }
+    }
+}
+
+fn search_from(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    graph: &LinkGraph,
+    value: CellValue,
+    start: usize,
+) {
+    // (node, is_true) -> previous state on the chain
+    let mut previous = vec![[None; 2]; graph.nodes.len()];
+    let mut queue = VecDeque::new();
+    previous[start][0] = Some((start, false));
+    queue.push_back((start, false, 0));
+
+    while let Some((node, is_true, links)) = queue.pop_front() {
+        let next_nodes = if is_true {
+            &graph.weak_links[node]
+        } else {
+            &graph.strong_links[node]
+        };
+        for &next in next_nodes {
+            let next_is_true = !is_true;
+            if previous[next][next_is_true as usize].is_some() {
+                continue;
+            }
+            previous[next][next_is_true as usize] = Some((node, is_true));
+            queue.push_back((next, next_is_true, links + 1));
+
+            // A chain of a single strong link is just a locked candidates or a conjugate pair
+            if !next_is_true || next == start || links + 1 < 3 {
+                continue;
+            }
+            let start_cells = &graph.nodes[start];
+            let end_cells = &graph.nodes[next];
+            let mut eliminated = sudoku.possible_cells(value) - start_cells;
+            eliminated -= end_cells;
+            for cell in start_cells.iter().chain(end_cells.iter()) {
+                eliminated &= sudoku.house_union_of_cell(cell);
+            }
+            if eliminated.is_empty() {
+                continue;
+            }
+
+            let mut chain = vec![(next, true)];
+            let mut state = (next, true);
+            while state != (start, false) {
+                state = previous[state.0][state.1 as usize].unwrap();
+                chain.push(state);
+            }
+            let chain_string = chain
+                .iter()
+                .rev()
+                .map(|&(node, _)| graph.node_to_string(sudoku, node))
+                .enumerate()
+                .map(|(i, node)| {
+                    if i == 0 {
+                        node
+                    } else if i % 2 == 1 {
+                        format!(" = {}", node)
+                    } else {
+                        format!(" - {}", node)
+                    }
+                })
+                .join("");
+            for cell in eliminated.iter() {
+                solution.add_elimination(
+                    Technique::GroupedXChain,
+                    format!("for {}, {}", value, chain_string),
+                    cell,
+                    value,
+                );
+            }
+            return_in_fast_mode!(solution);
+        }
+    }
+}
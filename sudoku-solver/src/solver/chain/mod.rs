@@ -1,3 +1,14 @@
+mod aic_type2;
 mod forced_chain;
+mod grouped_x_chain;
+mod nice_loop;
+mod xy_chain;
 
-pub use forced_chain::solve_forced_chain;
\ No newline at end of file
+pub use aic_type2::solve_aic_type2;
+pub use forced_chain::{
+    build_implication_graph, solve_forced_chain, solve_forced_chain_with_max_length, AssumptionIds,
+    Graph,
+};
+pub use grouped_x_chain::solve_grouped_x_chain;
+pub use nice_loop::solve_nice_loop;
+pub use xy_chain::{solve_xy_chain, solve_xy_chain_with_max_length, DEFAULT_MAX_XY_CHAIN_LENGTH};
@@ -0,0 +1,81 @@
+use crate::solver::chain::forced_chain::{build_implication_graph, Assumption};
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+
+use itertools::Itertools;
+use rustc_hash::FxHashSet;
+
+// Continuous Nice Loop：强链与弱链交替且首尾相接的环。
+// 若环中的 A 为真，沿环的一个方向可以推导出环中的每个节点；若 A 为假，沿另一个方向也可以推导出环中的每个节点。
+// 因此环中每条弱链的两端恰有一个为真，弱链成为强链：
+// 两端为不同单元格中的同一数字时，能同时看到两端的单元格中不可能出现该数字；
+// 两端为同一单元格中的两个数字时，该单元格中不可能出现其他数字。
+pub fn solve_nice_loop(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let (graph, _, _) = build_implication_graph(sudoku);
+    let mut eliminated = FxHashSet::default();
+
+    // 以弱链 on - off 闭合的环，即 off 可以推导出 on
+    for (on, off) in graph.weak_links() {
+        if graph.get_edge(off, on).is_none() {
+            continue;
+        }
+        let path = graph.path_nodes(off, on);
+        let loop_nodes = std::iter::once(on).chain(path).collect::<Vec<_>>();
+        let candidates = loop_nodes[1..]
+            .iter()
+            .map(|&node| (graph.get_node(node).cell(), graph.get_node(node).value()))
+            .collect::<Vec<_>>();
+        // 环至少包含四个候选数，且每个候选数只出现一次
+        if candidates.len() < 4 || !candidates.iter().all_unique() {
+            continue;
+        }
+
+        let reason = format!(
+            "continuous nice loop {} - {}",
+            graph.get_node(on).name(sudoku),
+            graph.path_to_string(sudoku, off, on),
+        );
+        let len = solution.steps.len();
+        for (&start, &end) in loop_nodes.iter().tuple_windows() {
+            if !graph.get_node(start).is_on() {
+                continue;
+            }
+            for (cell, value) in weak_link_eliminations(
+                sudoku,
+                graph.get_node(start),
+                graph.get_node(end),
+                &candidates,
+            ) {
+                if eliminated.insert((cell, value)) {
+                    solution.add_elimination(Technique::NiceLoop, reason.clone(), cell, value);
+                }
+            }
+        }
+        solution.highlight_steps_since(len, &graph.path_highlights(off, on));
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn weak_link_eliminations(
+    sudoku: &SudokuSolver,
+    start: &Assumption,
+    end: &Assumption,
+    candidates: &[(CellIndex, CellValue)],
+) -> Vec<(CellIndex, CellValue)> {
+    if start.cell() == end.cell() {
+        return sudoku
+            .candidates(start.cell())
+            .iter()
+            .filter(|&value| value != start.value() && value != end.value())
+            .map(|value| (start.cell(), value))
+            .collect();
+    }
+    let value = start.value();
+    let mut cells = sudoku.possible_cells(value) & sudoku.house_union_of_cell(start.cell());
+    cells &= sudoku.house_union_of_cell(end.cell());
+    cells
+        .iter()
+        .filter(|&cell| !candidates.contains(&(cell, value)))
+        .map(|cell| (cell, value))
+        .collect()
+}
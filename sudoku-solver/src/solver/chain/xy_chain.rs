@@ -0,0 +1,151 @@
+use crate::solver::wing::bivalue_cells;
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::CellSet;
+
+use itertools::Itertools;
+
+pub const DEFAULT_MAX_XY_CHAIN_LENGTH: usize = 8;
+
+// XY-Chain：由双值单元格组成的链，相邻的两个单元格互相可见且共享一个候选数。
+// 若首个单元格 A={x,y} 不为 x，则 A 为 y，与 A 相邻的 B={y,z} 不为 y，则 B 为 z，依此类推。
+// 若末尾单元格在前一个单元格为假时为 x，则首尾两个单元格中至少一个为 x，
+// 能同时看到首尾两个单元格的单元格中不可能出现 x。
+// 两个单元格的链即为 naked pair，三个单元格的链即为 XY-Wing，因此链长至少为 3。
+pub fn solve_xy_chain(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    solve_xy_chain_with_max_length(sudoku, solution, DEFAULT_MAX_XY_CHAIN_LENGTH);
+}
+
+/// Same as `solve_xy_chain`, but only considers chains of at most `max_length` cells.
+/// Shorter chains are searched first so that the reported chains are as short as possible.
+pub fn solve_xy_chain_with_max_length(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    max_length: usize,
+) {
+    let bivalue_cells = bivalue_cells(sudoku);
+    if bivalue_cells.size() < 3 {
+        return;
+    }
+
+    for length in 3..=max_length.min(bivalue_cells.size()) {
+        for start in bivalue_cells.iter() {
+            for start_value in sudoku.candidates(start).iter() {
+                let mut chain = vec![(start, start_value)];
+                search_from(sudoku, solution, &bivalue_cells, &mut chain, length);
+                return_in_fast_mode!(solution);
+            }
+        }
+    }
+}
+
+// chain 中每一项为 (cell, value)，表示该单元格不为 value 时的推导
+fn search_from(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    bivalue_cells: &CellSet,
+    chain: &mut Vec<(CellIndex, CellValue)>,
+    length: usize,
+) {
+    let &(cell, value) = chain.last().unwrap();
+    let other_value = other_candidate(sudoku, cell, value);
+
+    if chain.len() == length {
+        let (_, start_value) = chain[0];
+        if other_value == start_value {
+            record_eliminations(sudoku, solution, chain, start_value);
+        }
+        return;
+    }
+
+    let next_cells = bivalue_cells & sudoku.house_union_of_cell(cell);
+    for next in next_cells.iter() {
+        if !sudoku.can_fill(next, other_value) || chain.iter().any(|&(c, _)| c == next) {
+            continue;
+        }
+        chain.push((next, other_value));
+        search_from(sudoku, solution, bivalue_cells, chain, length);
+        chain.pop();
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn other_candidate(sudoku: &SudokuSolver, cell: CellIndex, value: CellValue) -> CellValue {
+    let mut candidates = sudoku.candidates(cell).clone();
+    candidates.delete(value);
+    candidates.single_value()
+}
+
+fn record_eliminations(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    chain: &[(CellIndex, CellValue)],
+    value: CellValue,
+) {
+    let start = chain[0].0;
+    let end = chain[chain.len() - 1].0;
+    let mut eliminated = sudoku.possible_cells(value) & sudoku.house_union_of_cell(start);
+    eliminated &= sudoku.house_union_of_cell(end);
+    eliminated -= &chain.iter().map(|&(cell, _)| cell).collect::<CellSet>();
+    if eliminated.is_empty() {
+        return;
+    }
+
+    let chain_string = chain
+        .iter()
+        .map(|&(cell, value)| {
+            format!(
+                "{}={}-{}",
+                sudoku.get_cell_name(cell),
+                value,
+                other_candidate(sudoku, cell, value)
+            )
+        })
+        .join(" ");
+    for cell in eliminated.iter() {
+        solution.add_elimination(
+            Technique::XYChain,
+            format!("for {}, {}", value, chain_string),
+            cell,
+            value,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Techniques;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_xy_chain() {
+        let sudoku = Sudoku::from_candidates(
+            "
+            8 19 5 6 3 2 7 4 19
+            4 19 7 58 58 19 26 3 26
+            6 3 2 47 47 19 19 5 8
+            23 7 8 1 2456 456 39 26 359
+            9 5 4 3 26 8 26 1 7
+            23 6 1 9 257 57 4 8 35
+            1 8 6 27 9 3 5 27 4
+            7 4 3 258 568 56 18 9 126
+            5 2 9 478 1 467 38 67 36
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let wings = Techniques::from(["w_wing", "xy_wing", "xyz_wing"].into_iter());
+        assert!(solver.solve_one_step(&wings).is_none());
+
+        let techniques = Techniques::from(["xy_chain"].into_iter());
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            solution.to_string(solver.sudoku()).trim(),
+            "[XYChain] for 7, r6c6=7-5 r6c9=5-3 r9c9=3-6 r9c8=6-7 => r9c6<>7"
+        );
+
+        let mut solution = SolutionRecorder::new();
+        solve_xy_chain_with_max_length(&solver, &mut solution, 3);
+        assert!(solution.is_empty());
+    }
+}
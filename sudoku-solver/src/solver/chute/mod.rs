@@ -0,0 +1,115 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::{combinations, CellSet, CombinationOptions, NamedCellSet};
+
+use std::iter::FromIterator;
+
+use arrayvec::ArrayVec;
+
+// 一个 chute（band 或 stack）由三个 block 和三条 line 组成，每个数字在 chute 中的三个 block 和三条 line 中各出现一次。
+// 若两个不在同一 block 也不在同一 line 的单元格都只包含 x 和 y，且剩下的 block 与剩下的 line 的交集中不可能出现 x，
+// 则这两个单元格不可能同时为 x，即至少一个为 y，因此能同时看到这两个单元格的单元格中不可能出现 y。
+pub fn solve_chute_remote_pair(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let bivalue_cells =
+        CellSet::from_iter(sudoku.cells().filter(|&c| sudoku.candidates(c).size() == 2));
+    if bivalue_cells.size() < 2 {
+        return;
+    }
+
+    for (chute_idx, band) in sudoku.cells_in_bands().iter().enumerate() {
+        let blocks = [chute_idx * 3, chute_idx * 3 + 1, chute_idx * 3 + 2];
+        search_chute(
+            sudoku,
+            solution,
+            &bivalue_cells,
+            band,
+            &sudoku.cells_in_rows()[chute_idx * 3..chute_idx * 3 + 3],
+            blocks,
+            |cell| sudoku.cell_position(cell).0 % 3,
+        );
+        return_in_fast_mode!(solution);
+    }
+    for (chute_idx, stack) in sudoku.cells_in_stacks().iter().enumerate() {
+        let blocks = [chute_idx, chute_idx + 3, chute_idx + 6];
+        search_chute(
+            sudoku,
+            solution,
+            &bivalue_cells,
+            stack,
+            &sudoku.cells_in_columns()[chute_idx * 3..chute_idx * 3 + 3],
+            blocks,
+            |cell| sudoku.cell_position(cell).1 % 3,
+        );
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn search_chute(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    bivalue_cells: &CellSet,
+    chute: &NamedCellSet,
+    lines: &[NamedCellSet],
+    blocks: [usize; 3],
+    line_of_cell: impl Fn(CellIndex) -> usize,
+) {
+    let block_of_cell = |cell: CellIndex| {
+        let block = sudoku.cell_position(cell).2;
+        blocks.iter().position(|&b| b == block).unwrap()
+    };
+
+    let cells = ArrayVec::<_, 27>::from_iter((bivalue_cells & chute).iter());
+    for pair in combinations(&cells, 2, CombinationOptions::default()) {
+        let cell_a = pair[0];
+        let cell_b = pair[1];
+        if sudoku.candidates(cell_a) != sudoku.candidates(cell_b) {
+            continue;
+        }
+        let (line_a, line_b) = (line_of_cell(cell_a), line_of_cell(cell_b));
+        let (block_a, block_b) = (block_of_cell(cell_a), block_of_cell(cell_b));
+        if line_a == line_b || block_a == block_b {
+            continue;
+        }
+
+        // The remaining line and block of the chute are the ones not used by the pair.
+        let line = &lines[3 - line_a - line_b];
+        let block = &sudoku.cells_in_blocks()[blocks[3 - block_a - block_b]];
+        let intersection = line & block;
+
+        let common_peers = sudoku.house_union_of_cell(cell_a) & sudoku.house_union_of_cell(cell_b);
+        let value_1 = sudoku.candidates(cell_a)[0];
+        let value_2 = sudoku.candidates(cell_a)[1];
+        for (absent, eliminated) in [(value_1, value_2), (value_2, value_1)] {
+            if can_appear_in(sudoku, &intersection, absent) {
+                continue;
+            }
+            for cell in (&common_peers & sudoku.possible_cells(eliminated)).iter() {
+                solution.add_elimination(
+                    Technique::ChuteRemotePair,
+                    format!(
+                        "in {}, {} and {} only contain {},{} and {} cannot be in {} & {}, so one of them must be {}",
+                        chute.name(),
+                        sudoku.get_cell_name(cell_a),
+                        sudoku.get_cell_name(cell_b),
+                        value_1,
+                        value_2,
+                        absent,
+                        line.name(),
+                        block.name(),
+                        eliminated,
+                    ),
+                    cell,
+                    eliminated,
+                );
+            }
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+fn can_appear_in(sudoku: &SudokuSolver, cells: &CellSet, value: CellValue) -> bool {
+    !(cells & sudoku.possible_cells(value)).is_empty()
+        || cells
+            .iter()
+            .any(|cell| sudoku.cell_value(cell) == Some(value))
+}
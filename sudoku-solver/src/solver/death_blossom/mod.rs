@@ -0,0 +1,115 @@
+use crate::solver::als::{find_als, Als};
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::CellSet;
+
+use itertools::Itertools;
+
+// Death Blossom：stem 单元格的每个候选数 s 对应一个 petal ALS，ALS 中所有 s 所在的单元格都能看到 stem。
+// stem 必为其中某个候选数 s，此时对应的 petal 中不能出现 s，成为 locked set。
+// 若所有 petal 都包含 stem 中没有的数字 z，则 z 必在某个 petal 中，
+// 能看到所有 petal 中 z 所在单元格的单元格中不可能出现 z。
+pub fn solve_death_blossom(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let all_als = find_als(sudoku, 8);
+    for stem in sudoku.unfilled_cells().iter() {
+        let stem_values = sudoku.candidates(stem);
+        if !(2..=3).contains(&stem_values.size()) {
+            continue;
+        }
+
+        // 每个候选数可用的 petal
+        let petals = stem_values
+            .iter()
+            .map(|value| {
+                all_als
+                    .iter()
+                    .filter(|als| is_petal(sudoku, als, stem, value))
+                    .collect_vec()
+            })
+            .collect_vec();
+        if petals.iter().any(|petals| petals.is_empty()) {
+            continue;
+        }
+
+        for z in (1..=9).filter(|&z| !stem_values.has(z)) {
+            let petals_with_z = petals
+                .iter()
+                .map(|petals| {
+                    petals
+                        .iter()
+                        .copied()
+                        .filter(|als| als.values.has(z))
+                        .collect_vec()
+                })
+                .collect_vec();
+            if petals_with_z.iter().any(|petals| petals.is_empty()) {
+                continue;
+            }
+
+            let mut chosen = vec![];
+            search_petals(
+                sudoku,
+                solution,
+                stem,
+                z,
+                &petals_with_z,
+                &mut chosen,
+                sudoku.possible_cells(z).clone(),
+            );
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+fn is_petal(sudoku: &SudokuSolver, als: &Als, stem: CellIndex, value: CellValue) -> bool {
+    if als.cells.has(stem) || !als.values.has(value) {
+        return false;
+    }
+    als.cells_with(sudoku, value)
+        .is_subset_of(sudoku.house_union_of_cell(stem))
+}
+
+fn search_petals<'a>(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    stem: CellIndex,
+    z: CellValue,
+    petals: &[Vec<&'a Als>],
+    chosen: &mut Vec<&'a Als>,
+    eliminated: CellSet,
+) {
+    if eliminated.is_empty() {
+        return;
+    }
+    if chosen.len() == petals.len() {
+        let petal_cells = CellSet::union_multiple(chosen.iter().map(|als| &*als.cells));
+        let eliminated = &eliminated - &petal_cells;
+        let reason = format!(
+            "the stem {}({}) with the petals {}, so one of the petals contains {}",
+            sudoku.get_cell_name(stem),
+            sudoku.candidates(stem).iter().join(""),
+            sudoku
+                .candidates(stem)
+                .iter()
+                .zip(chosen.iter())
+                .map(|(value, als)| format!("{}: {}", value, als.to_string(sudoku)))
+                .join(", "),
+            z,
+        );
+        for cell in eliminated.iter() {
+            solution.add_elimination(Technique::DeathBlossom, reason.clone(), cell, z);
+        }
+        return;
+    }
+
+    for &als in petals[chosen.len()].iter() {
+        let mut next_eliminated = eliminated.clone();
+        for cell in als.cells_with(sudoku, z).iter() {
+            next_eliminated &= sudoku.house_union_of_cell(cell);
+        }
+        chosen.push(als);
+        search_petals(sudoku, solution, stem, z, petals, chosen, next_eliminated);
+        chosen.pop();
+        return_in_fast_mode!(solution);
+    }
+}
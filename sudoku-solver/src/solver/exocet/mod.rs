@@ -0,0 +1,175 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::{CellSet, NamedCellSet, ValueSet};
+
+use itertools::Itertools;
+
+// Junior Exocet（以横向 band 为例，纵向 stack 同理）：
+// base cells: 同一 block 同一行中的两个单元格，候选数并集（base digits）为 2 到 4 个。
+// target cells: 位于 band 中另外两个 block 且分别位于另外两行中的两个单元格。
+// cross lines: 两个 target 所在的列，以及 base block 中不包含 base cells 的那一列。
+// 对每个 base digit d：
+//   1. 在 band 外的 cross lines 中，d 的位置可以被两个 house 覆盖，即 band 外至多出现两次；
+//   2. 与 target 同列、位于另一个 target 所在行的 mirror 单元格中不可能出现 d。
+// 则 d 必须在 band 内的 cross lines 中出现，而只可能出现在 target 中。
+// base cells 中的两个数字不同，因此 target cells 恰好为 base cells 中的两个数字：
+// target cells 中的非 base digit 可以删除，不在任何 target 中出现的 base digit 可以从 base cells 中删除。
+pub fn solve_exocet(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for chute in 0..3 {
+        // band 中 line 为行、cross line 为列；stack 中 line 为列、cross line 为行
+        search_chute(
+            sudoku,
+            solution,
+            chute,
+            sudoku.cells_in_rows(),
+            sudoku.cells_in_columns(),
+            |line, pos| sudoku.cell_index(line, pos),
+        );
+        return_in_fast_mode!(solution);
+        search_chute(
+            sudoku,
+            solution,
+            chute,
+            sudoku.cells_in_columns(),
+            sudoku.cells_in_rows(),
+            |line, pos| sudoku.cell_index(pos, line),
+        );
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn search_chute(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    chute: usize,
+    lines: &[NamedCellSet],
+    cross_lines: &[NamedCellSet],
+    cell_at: impl Fn(usize, usize) -> CellIndex,
+) {
+    let chute_lines = [chute * 3, chute * 3 + 1, chute * 3 + 2];
+    for base_box in 0..3 {
+        for &base_line in chute_lines.iter() {
+            for free_pos in 0..3 {
+                let base_positions = (0..3).filter(|&p| p != free_pos).map(|p| base_box * 3 + p);
+                let base_cells: Vec<_> =
+                    base_positions.map(|pos| cell_at(base_line, pos)).collect();
+                if base_cells.iter().any(|&c| sudoku.cell_value(c).is_some()) {
+                    continue;
+                }
+                let base_digits =
+                    sudoku.candidates(base_cells[0]) | sudoku.candidates(base_cells[1]);
+                if !(2..=4).contains(&base_digits.size()) {
+                    continue;
+                }
+
+                let other_lines: Vec<_> = chute_lines
+                    .iter()
+                    .copied()
+                    .filter(|&l| l != base_line)
+                    .collect();
+                let other_boxes: Vec<_> = (0..3).filter(|&b| b != base_box).collect();
+                for (line1, line2) in [
+                    (other_lines[0], other_lines[1]),
+                    (other_lines[1], other_lines[0]),
+                ] {
+                    for pos1 in other_boxes[0] * 3..other_boxes[0] * 3 + 3 {
+                        for pos2 in other_boxes[1] * 3..other_boxes[1] * 3 + 3 {
+                            let target1 = cell_at(line1, pos1);
+                            let target2 = cell_at(line2, pos2);
+                            if !is_possible_target(sudoku, target1, &base_digits)
+                                || !is_possible_target(sudoku, target2, &base_digits)
+                            {
+                                continue;
+                            }
+
+                            let cross = [base_box * 3 + free_pos, pos1, pos2];
+                            let mirrors = [cell_at(line2, pos1), cell_at(line1, pos2)];
+                            let mut s_cells =
+                                CellSet::union_multiple(cross.iter().map(|&p| &*cross_lines[p]));
+                            for &line in chute_lines.iter() {
+                                s_cells -= &lines[line];
+                            }
+                            let cover_houses: Vec<&NamedCellSet> = (0..9)
+                                .filter(|line| !chute_lines.contains(line))
+                                .map(|line| &lines[line])
+                                .chain(cross.iter().map(|&p| &cross_lines[p]))
+                                .collect();
+
+                            let is_exocet = base_digits.iter().all(|digit| {
+                                mirrors.iter().all(|&m| !can_hold(sudoku, m, digit))
+                                    && is_covered_by_two_houses(
+                                        &digit_cells(sudoku, &s_cells, digit),
+                                        &cover_houses,
+                                    )
+                            });
+                            if !is_exocet {
+                                continue;
+                            }
+
+                            record_eliminations(
+                                sudoku,
+                                solution,
+                                &base_cells,
+                                [target1, target2],
+                                &base_digits,
+                            );
+                            return_in_fast_mode!(solution);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn is_possible_target(sudoku: &SudokuSolver, cell: CellIndex, base_digits: &ValueSet) -> bool {
+    sudoku.cell_value(cell).is_none() && !(sudoku.candidates(cell) & base_digits).is_empty()
+}
+
+fn can_hold(sudoku: &SudokuSolver, cell: CellIndex, value: CellValue) -> bool {
+    sudoku.cell_value(cell) == Some(value) || sudoku.can_fill(cell, value)
+}
+
+fn digit_cells(sudoku: &SudokuSolver, cells: &CellSet, value: CellValue) -> CellSet {
+    cells
+        .iter()
+        .filter(|&cell| can_hold(sudoku, cell, value))
+        .collect()
+}
+
+fn is_covered_by_two_houses(cells: &CellSet, houses: &[&NamedCellSet]) -> bool {
+    for (i, house1) in houses.iter().enumerate() {
+        for house2 in houses.iter().take(i) {
+            if cells.is_subset_of(&(*house1 | *house2)) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn record_eliminations(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    base_cells: &[CellIndex],
+    target_cells: [CellIndex; 2],
+    base_digits: &ValueSet,
+) {
+    let reason = format!(
+        "junior exocet with base cells {} and target cells {}, base digits are {}",
+        sudoku.get_cellset_string(&base_cells.iter().copied().collect()),
+        sudoku.get_cellset_string(&target_cells.iter().copied().collect()),
+        base_digits.iter().join(","),
+    );
+    for &cell in target_cells.iter() {
+        for value in (sudoku.candidates(cell) - base_digits).iter() {
+            solution.add_elimination(Technique::Exocet, reason.clone(), cell, value);
+        }
+    }
+    let target_digits = sudoku.candidates(target_cells[0]) | sudoku.candidates(target_cells[1]);
+    for &cell in base_cells.iter() {
+        for value in (sudoku.candidates(cell) - &target_digits).iter() {
+            solution.add_elimination(Technique::Exocet, reason.clone(), cell, value);
+        }
+    }
+}
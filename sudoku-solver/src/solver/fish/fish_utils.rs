@@ -1,4 +1,4 @@
-use crate::solver::{SolutionRecorder, SudokuSolver, Technique};
+use crate::solver::{CellHighlight, HighlightRole, SolutionRecorder, SudokuSolver, Technique};
 use crate::sudoku::CellValue;
 use crate::utils::{CellSet, NamedCellSet};
 
@@ -21,17 +21,38 @@ pub fn check_is_fish(
         return;
     }
 
-    let allow_fins = rule != Technique::BasicFish;
+    let allow_fins = !matches!(
+        rule,
+        Technique::BasicFish | Technique::XWing | Technique::Swordfish | Technique::Jellyfish
+    );
     if !allow_fins && !fins.is_empty() {
         return;
     }
     for fin in fins.iter() {
-        eliminated_cells &= &sudoku.house_union_of_cell[fin as usize];
+        eliminated_cells &= sudoku.house_union_of_cell(fin);
     }
     if eliminated_cells.is_empty() {
         return;
     }
 
+    // 去掉 fins 后，若某个 base house 中至多只剩一个位置，则即使没有 fins 也无法构成鱼，称为 sashimi fish
+    let rule = match rule {
+        Technique::FinnedFish | Technique::SashimiFish => {
+            let is_sashimi = !fins.is_empty()
+                && base_set.iter().any(|&house| {
+                    let base_cells: &CellSet = house;
+                    (base_cells & cover_cells).size() <= 1
+                });
+            match (rule, is_sashimi) {
+                (Technique::SashimiFish, false) => return,
+                (_, true) => Technique::SashimiFish,
+                (rule, false) => rule,
+            }
+        }
+        rule => rule,
+    };
+
+    let highlights = fish_highlights(base_cells, cover_cells, value);
     for cell in eliminated_cells.iter() {
         let reason = if fins.is_empty() {
             format!(
@@ -49,6 +70,35 @@ pub fn check_is_fish(
                 sudoku.get_cellset_string(&fins),
             )
         };
-        solution.add_elimination(rule.clone(), reason, cell, value);
+        solution.add_fish_elimination(
+            rule.clone(),
+            reason,
+            cell,
+            value,
+            base_set.len(),
+            &highlights,
+        );
     }
 }
+
+/// The candidates of a fish on `value`: the base cells inside the cover sets, the cover cells
+/// outside the base sets, and the fins.
+pub fn fish_highlights(
+    base_cells: &CellSet,
+    cover_cells: &CellSet,
+    value: CellValue,
+) -> Vec<CellHighlight> {
+    [
+        (base_cells & cover_cells, HighlightRole::Base),
+        (cover_cells - base_cells, HighlightRole::Cover),
+        (base_cells - cover_cells, HighlightRole::Fin),
+    ]
+    .into_iter()
+    .flat_map(|(cells, role)| {
+        cells
+            .iter()
+            .map(|cell| CellHighlight { cell, value, role })
+            .collect_vec()
+    })
+    .collect()
+}
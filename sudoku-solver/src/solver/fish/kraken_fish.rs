@@ -0,0 +1,108 @@
+use super::fish_utils::fish_highlights;
+use crate::solver::chain::{AssumptionIds, Graph};
+use crate::solver::return_in_fast_mode;
+use crate::solver::{SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::CellValue;
+use crate::utils::{comb, CellSet, NamedCellSet};
+
+use std::iter::FromIterator;
+
+use arrayvec::ArrayVec;
+use itertools::Itertools;
+
+pub fn search_kraken_fish(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    graph: &Graph,
+    on_assumptions: &AssumptionIds,
+    off_assumptions: &AssumptionIds,
+    size: usize,
+    value: CellValue,
+) {
+    let rows = ArrayVec::<_, 9>::from_iter(
+        sudoku
+            .cells_in_rows()
+            .iter()
+            .map(|s| sudoku.get_possible_cells_for_house_and_value(s, value))
+            .filter(|s| s.size() > 1),
+    );
+    let cols = ArrayVec::<_, 9>::from_iter(
+        sudoku
+            .cells_in_columns()
+            .iter()
+            .map(|s| sudoku.get_possible_cells_for_house_and_value(s, value))
+            .filter(|s| s.size() > 1),
+    );
+
+    let row_sets = comb(&rows, size)
+        .map(|row_set| {
+            let row_cells = CellSet::union_multiple(row_set.iter().map(|r| &***r));
+            (row_set, row_cells)
+        })
+        .collect_vec();
+    let col_sets = comb(&cols, size)
+        .map(|col_set| {
+            let col_cells = CellSet::union_multiple(col_set.iter().map(|c| &***c));
+            (col_set, col_cells)
+        })
+        .collect_vec();
+
+    let check = |solution: &mut SolutionRecorder,
+                 base_set: &[&NamedCellSet],
+                 cover_set: &[&NamedCellSet],
+                 base_cells: &CellSet,
+                 cover_cells: &CellSet| {
+        let fins = base_cells - cover_cells;
+        if fins.is_empty() {
+            return;
+        }
+        let highlights = fish_highlights(base_cells, cover_cells, value);
+        for cell in (cover_cells - base_cells).iter() {
+            // 能看到所有 fins 的单元格已经由 finned fish 删除
+            if fins
+                .iter()
+                .all(|fin| sudoku.house_union_of_cell(fin).has(cell))
+            {
+                continue;
+            }
+            let off = off_assumptions[cell as usize][value as usize - 1].unwrap();
+            let on_fins = fins
+                .iter()
+                .map(|fin| on_assumptions[fin as usize][value as usize - 1].unwrap())
+                .collect_vec();
+            if on_fins.iter().any(|&on| graph.get_edge(on, off).is_none()) {
+                continue;
+            }
+            let reason = format!(
+                "for {}, {} is covered by {} with fins {}, and every fin forces {}<>{}: {}",
+                value,
+                base_set.iter().map(|s| s.name()).join(","),
+                cover_set.iter().map(|s| s.name()).join(","),
+                sudoku.get_cellset_string(&fins),
+                sudoku.get_cell_name(cell),
+                value,
+                on_fins
+                    .iter()
+                    .map(|&on| graph.path_to_string(sudoku, on, off))
+                    .join("; "),
+            );
+            solution.add_fish_elimination(
+                Technique::KrakenFish,
+                reason,
+                cell,
+                value,
+                base_set.len(),
+                &highlights,
+            );
+        }
+    };
+
+    for (row_set, row_cells) in &row_sets {
+        for (col_set, col_cells) in &col_sets {
+            check(solution, row_set, col_set, row_cells, col_cells);
+            return_in_fast_mode!(solution);
+            check(solution, col_set, row_set, col_cells, row_cells);
+            return_in_fast_mode!(solution);
+        }
+    }
+}
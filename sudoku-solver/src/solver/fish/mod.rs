@@ -1,8 +1,10 @@
 mod fish_utils;
 mod franken_fish;
+mod kraken_fish;
 mod mutant_fish;
 mod simple_fish;
 
+use crate::solver::chain::build_implication_graph;
 use crate::solver::return_in_fast_mode;
 use crate::solver::{SolutionRecorder, SudokuSolver, Technique};
 
@@ -18,6 +20,31 @@ pub fn solve_basic_fish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder)
     }
 }
 
+// 与 BasicFish 相同，但只搜索特定大小的鱼，并以对应的名称记录：X-Wing 为 2，Swordfish 为 3，Jellyfish 为 4。
+pub fn solve_x_wing(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    solve_sized_basic_fish(sudoku, solution, 2, Technique::XWing);
+}
+
+pub fn solve_swordfish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    solve_sized_basic_fish(sudoku, solution, 3, Technique::Swordfish);
+}
+
+pub fn solve_jellyfish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    solve_sized_basic_fish(sudoku, solution, 4, Technique::Jellyfish);
+}
+
+fn solve_sized_basic_fish(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    size: usize,
+    technique: Technique,
+) {
+    for value in 1..=9 {
+        simple_fish::search_simple_fish(sudoku, solution, size, value, technique.clone());
+        return_in_fast_mode!(solution);
+    }
+}
+
 pub fn solve_finned_fish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
     for size in 2..=4 {
         for value in 1..=9 {
@@ -27,6 +54,16 @@ pub fn solve_finned_fish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder)
     }
 }
 
+// 只搜索 sashimi fish，即 fins 替代了某个 base house 中缺失的位置的 finned fish。
+pub fn solve_sashimi_fish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for size in 2..=4 {
+        for value in 1..=9 {
+            simple_fish::search_simple_fish(sudoku, solution, size, value, Technique::SashimiFish);
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
 pub fn solve_franken_fish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
     // Every Franken X-Wing is degenerate to a finned X-Wing.
     for size in 3..=4 {
@@ -45,3 +82,131 @@ pub fn solve_mutant_fish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder)
         }
     }
 }
+
+// Kraken Fish：不能看到所有 fins 的 cover 单元格无法由 finned fish 删除。
+// 鱼成立或某个 fin 为真，二者必居其一。若每个 fin 为真时都能通过链推出该单元格不为该数字，则该单元格不可能为该数字。
+pub fn solve_kraken_fish(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let (graph, on_assumptions, off_assumptions) = build_implication_graph(sudoku);
+    for size in 2..=4 {
+        for value in 1..=9 {
+            kraken_fish::search_kraken_fish(
+                sudoku,
+                solution,
+                &graph,
+                &on_assumptions,
+                &off_assumptions,
+                size,
+                value,
+            );
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Techniques;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_sashimi_fish() {
+        let sudoku = Sudoku::from_candidates(
+            "
+            6 1 25 7 3 9 8 4 25
+            89 459 3 468 2468 2468 259 1 7
+            7 49 28 148 5 1248 29 3 6
+            1 59 4 3 28 28 7 6 59
+            2 6 7 149 14 145 345 8 3459
+            89 3 58 469 7 456 1 2 459
+            3 28 6 5 9 148 24 7 1248
+            5 28 1 468 468 7 234 9 2348
+            4 7 9 2 18 3 6 5 18
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        for technique in ["finned_fish", "sashimi_fish"] {
+            let techniques = Techniques::from([technique].into_iter());
+            let solution = solver.solve_one_step(&techniques).unwrap();
+            assert_eq!(
+                solution.to_string(solver.sudoku()).trim(),
+                "[SashimiFish] for 5, r1,r4 is covered by c2,c9 with fins r1c3 => r2c2<>5"
+            );
+            assert_eq!(solution.steps[0].technique_name(), "Sashimi X-Wing");
+        }
+    }
+
+    #[test]
+    fn test_finned_fish_with_two_fins() {
+        let techniques = Techniques::from(["finned_fish"].into_iter());
+        // A Swordfish on 1 in r1,r4,r7 with the fins r1c2 and r1c3 in b1, only r2c1 sees both fins
+        let sudoku = Sudoku::from_candidates(
+            "
+            123 123 123 123 23 23 123 23 23
+            123 23 23 23 23 23 23 23 23
+            23 23 23 123 23 23 23 23 23
+            123 23 23 123 23 23 23 23 23
+            23 23 23 23 23 23 123 23 23
+            23 23 23 23 23 23 23 23 23
+            23 23 23 123 23 23 123 23 23
+            23 23 23 23 23 23 23 23 23
+            23 23 23 23 23 23 23 23 23
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            solution.to_string(solver.sudoku()).trim(),
+            "[FinnedFish] for 1, r1,r4,r7 is covered by c1,c4,c7 with fins r1c2,r1c3 => r2c1<>1"
+        );
+        assert_eq!(solution.steps[0].technique_name(), "Finned Swordfish");
+
+        // With the fins r1c2 and r1c5 in different blocks, no cell of the cover set sees both fins
+        let sudoku = Sudoku::from_candidates(
+            "
+            123 123 23 123 123 23 123 23 23
+            123 23 23 23 23 23 23 23 23
+            23 23 23 123 23 23 23 23 23
+            123 23 23 123 23 23 23 23 23
+            23 23 23 23 23 23 123 23 23
+            23 23 23 23 23 23 23 23 23
+            23 23 23 123 23 23 123 23 23
+            23 23 23 23 23 23 23 23 23
+            23 23 23 23 23 23 23 23 23
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        assert!(solver.solve_one_step(&techniques).is_none());
+    }
+
+    #[test]
+    fn test_fish_names() {
+        let sudoku = Sudoku::from_candidates(
+            "
+            2 678 478 9 1347 1367 17 5 147
+            1 67 457 2 47 567 8 3 9
+            3 9 457 457 8 157 6 2 147
+            78 5 3 478 147 178 2 9 6
+            6 17 9 3 5 2 17 4 8
+            4 178 2 6 9 78 3 17 5
+            58 2 1 58 37 9 4 6 37
+            9 3 78 1 6 4 5 78 2
+            578 4 6 578 2 3578 9 178 137
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let techniques = Techniques::from(["franken_fish"].into_iter());
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            solution.to_string(solver.sudoku()).trim(),
+            "[FrankenFish] for 7, c1,c4,c5 is covered by r4,b2,b8 with fins r9c1 => r9c6<>7"
+        );
+        assert_eq!(solution.steps[0].size, 3);
+        assert_eq!(solution.steps[0].technique_name(), "Franken Swordfish");
+
+        assert_eq!(Technique::BasicFish.name_with_size(2), "X-Wing");
+        assert_eq!(Technique::from("swordfish").name_with_size(3), "Swordfish");
+        assert_eq!(Technique::MutantFish.name_with_size(4), "Mutant Jellyfish");
+        assert_eq!(Technique::NakedSingle.name_with_size(0), "NakedSingle");
+    }
+}
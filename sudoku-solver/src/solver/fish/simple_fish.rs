@@ -19,7 +19,15 @@ pub fn search_simple_fish(
 ) {
     debug_assert!(size >= 2 && size <= 4);
     debug_assert!(value >= 1 && value <= 9);
-    debug_assert!(rule == Technique::BasicFish || rule == Technique::FinnedFish);
+    debug_assert!(matches!(
+        rule,
+        Technique::BasicFish
+            | Technique::XWing
+            | Technique::Swordfish
+            | Technique::Jellyfish
+            | Technique::FinnedFish
+            | Technique::SashimiFish
+    ));
 
     let rows_in_size = ArrayVec::<_, 9>::from_iter(
         sudoku
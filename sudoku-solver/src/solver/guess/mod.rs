@@ -992,6 +992,17 @@ static CACHE: LazyLock<Cache> = LazyLock::new(|| {
     }
 });
 
+/// A branch taken by `State::solve_with_trace`. The configurations of the digits in
+/// `configuration_value_mask` in the band are split in two: `chosen` is true for the branch keeping
+/// only the first of them, and false for the branch eliminating it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchDecision {
+    pub is_vertical: bool,
+    pub band_idx: usize,
+    pub configuration_value_mask: u16,
+    pub chosen: bool,
+}
+
 /// The state of the sudoku board.
 ///
 /// There are two different representations of the state: the blocks and the bands.
@@ -1058,6 +1069,8 @@ pub struct State {
     /// The second dimension is the index of the band (3 bands in a board).
     bands: [[Band; 3]; 2],
     blocks: [Block; 9],
+    /// The cell (or the first cell of the triad) whose candidates ran out in the last contradiction.
+    contradiction: Option<u8>,
 }
 
 const MINIMUM_COUNT_OF_CANDIDATES_IN_BLOCK: u16x16 =
@@ -1068,13 +1081,14 @@ impl State {
         Self {
             bands: array::from_fn(|_| array::from_fn(|_| Band::new())),
             blocks: array::from_fn(|_| Block::new()),
+            contradiction: None,
         }
     }
 
     pub fn from_values(values: &str) -> Self {
         let mut state = Self::new();
         for (pos, c) in values.chars().enumerate() {
-            if c != '.' && c != '0' {
+            if !is_empty_value(c) {
                 state.fill(pos as u8, c.to_digit(10).unwrap() as u8 - 1);
             }
         }
@@ -1198,7 +1212,16 @@ impl State {
             // );
 
             let counts = block.simd_count_ones();
-            if counts.simd_lt(MINIMUM_COUNT_OF_CANDIDATES_IN_BLOCK).any() {
+            let invalid = counts.simd_lt(MINIMUM_COUNT_OF_CANDIDATES_IN_BLOCK);
+            if invalid.any() {
+                let lane = invalid.to_bitmask().trailing_zeros() as usize;
+                // Map the cell or the negative triad back to a cell in the board
+                let (r, c) = match lane {
+                    0..12 if lane % 4 != 3 => (lane / 4, lane % 4),
+                    0..12 => (lane / 4, 0),
+                    _ => (0, lane - 12),
+                };
+                self.contradiction = Some(((block_r * 3 + r) * 9 + block_c * 3 + c) as u8);
                 return Err(());
             }
 
@@ -1413,14 +1436,76 @@ impl State {
         return Block(asserting_cells);
     }
 
+    /// Same as `solve`, but returns the cell whose candidates first ran out if the sudoku has no solution.
+    /// A contradiction in a triad is reported as the first cell of the triad.
+    pub fn solve_with_blame(&mut self) -> Result<(), CellIndex> {
+        if let Some(cell) = self.contradiction {
+            return Err(cell as CellIndex);
+        }
+        // Every failure comes from `block_restrict`, which records the contradiction
+        self.solve()
+            .map_err(|_| self.contradiction.expect("contradiction is recorded") as CellIndex)
+    }
+
     pub fn solve(&mut self) -> Result<(), ()> {
+        self.solve_inner::<false>(&mut Vec::new())
+    }
+
+    /// Same as `solve`, but also returns every branch taken during the search in order, including
+    /// the ones that led to a contradiction. The trace is empty if no guess was needed.
+    pub fn solve_with_trace(&mut self) -> (Result<(), ()>, Vec<BranchDecision>) {
+        let mut trace = vec![];
+        let result = self.solve_inner::<true>(&mut trace);
+        (result, trace)
+    }
+
+    fn solve_inner<const TRACE: bool>(
+        &mut self,
+        trace: &mut Vec<BranchDecision>,
+    ) -> Result<(), ()> {
         if let Some((is_vertical, band_idx, configuration_value_mask)) = self.choose_branch_point()
         {
-            return self.branch(is_vertical, band_idx, configuration_value_mask);
+            return self.branch::<TRACE>(is_vertical, band_idx, configuration_value_mask, trace);
         }
         Ok(())
     }
 
+    /// Reads back the candidates of each cell as a bitmask, where bit `i` stands for value `i + 1`.
+    fn candidates_grid(&self) -> [u16; 81] {
+        array::from_fn(|i| {
+            let block_index = BlockIndex::from_cell(i as u8);
+            self.blocks[block_index.block_idx as usize].0.as_array()
+                [block_index.element_idx as usize]
+        })
+    }
+
+    /// The values of the cells as a string of 81 characters, where a cell with more than one
+    /// candidate is `.`. After a successful `solve`, this is the solution.
+    pub fn to_value_string(&self) -> String {
+        self.candidates_grid()
+            .into_iter()
+            .map(|bits| {
+                if bits.count_ones() == 1 {
+                    char::from(b'1' + bits.trailing_zeros() as u8)
+                } else {
+                    '.'
+                }
+            })
+            .collect()
+    }
+
+    /// Converts the state into a candidate grid, regardless of whether `solve` succeeded.
+    /// After a contradiction, the grid shows how far the propagation got and may be inconsistent.
+    pub fn into_sudoku(self) -> Sudoku {
+        let mut sudoku = Sudoku::empty();
+        for (i, bits) in self.candidates_grid().into_iter().enumerate() {
+            for j in (0..9).filter(|&j| (1 << j) & bits != 0) {
+                sudoku.add_candidate(i as CellIndex, j + 1);
+            }
+        }
+        sudoku
+    }
+
     fn choose_branch_point(&self) -> Option<(bool, usize, u16)> {
         fn count_ones(v: u16x8) -> u16 {
             unsafe { std::intrinsics::simd::simd_ctpop(v).reduce_sum() }
@@ -1495,17 +1580,18 @@ impl State {
         None
     }
 
-    fn branch(
-        &mut self,
+    /// Splits the configurations of the band containing the digits in `configuration_value_mask` into two
+    /// branches. Returns the eliminations of the first branch, which keeps only one of the configurations,
+    /// and those of the second branch, which eliminates that configuration.
+    fn split_configurations(
+        &self,
         is_vertical: bool,
         band_idx: usize,
         configuration_value_mask: u16,
-    ) -> Result<(), ()> {
+    ) -> (u16x8, u16x8) {
         let candidates = self.bands[is_vertical as usize][band_idx].configurations.0
             & u16x8::splat(configuration_value_mask);
 
-        // Try to eliminate one of the configurations and see if the board is still solvable.
-        let mut state_copy = self.clone();
         let has_values = candidates.simd_ne(u16x8::splat(0)).to_array();
         let mut configurations = None;
         for i in 0..8 {
@@ -1521,27 +1607,151 @@ impl State {
             }
         }
         let configurations = configurations.unwrap();
+        (configurations, candidates ^ configurations)
+    }
+
+    fn branch<const TRACE: bool>(
+        &mut self,
+        is_vertical: bool,
+        band_idx: usize,
+        configuration_value_mask: u16,
+        trace: &mut Vec<BranchDecision>,
+    ) -> Result<(), ()> {
+        let (first_eliminations, second_eliminations) =
+            self.split_configurations(is_vertical, band_idx, configuration_value_mask);
+        let decision = |chosen| BranchDecision {
+            is_vertical,
+            band_idx,
+            configuration_value_mask,
+            chosen,
+        };
+
+        // Try to eliminate one of the configurations and see if the board is still solvable.
+        if TRACE {
+            trace.push(decision(true));
+        }
+        let mut state_copy = self.clone();
         state_copy.bands[is_vertical as usize][band_idx]
             .eliminations
-            .0 |= configurations;
+            .0 |= first_eliminations;
         if state_copy
             .band_elimination(is_vertical, band_idx, 0)
             .is_ok()
         {
-            if state_copy.solve().is_ok() {
+            if state_copy.solve_inner::<TRACE>(trace).is_ok() {
                 *self = state_copy;
                 return Ok(());
             }
         }
 
         // Try to assert the configuration and see if the board is still solvable.
-        self.bands[is_vertical as usize][band_idx].eliminations.0 |= candidates ^ configurations;
+        if TRACE {
+            trace.push(decision(false));
+        }
+        self.bands[is_vertical as usize][band_idx].eliminations.0 |= second_eliminations;
         if self.band_elimination(is_vertical, band_idx, 0).is_ok() {
-            return self.solve();
+            return self.solve_inner::<TRACE>(trace);
         }
 
         Err(())
     }
+
+    /// Same as `solve`, but explores both directions of each branch concurrently and keeps the one
+    /// that finds a solution first. The other direction is cancelled as soon as a solution is
+    /// found, so for a sudoku with several solutions the result may differ from `solve`.
+    #[cfg(feature = "parallel")]
+    pub fn solve_parallel(&mut self) -> Result<(), ()> {
+        use std::sync::atomic::AtomicBool;
+
+        self.solve_parallel_inner(&AtomicBool::new(false), 0)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn solve_parallel_inner(
+        &mut self,
+        solved: &std::sync::atomic::AtomicBool,
+        depth: usize,
+    ) -> Result<(), ()> {
+        use std::sync::atomic::Ordering;
+
+        // Spawning tasks for the small subtrees near the leaves costs more than it saves
+        const MAX_PARALLEL_DEPTH: usize = 8;
+
+        if solved.load(Ordering::Relaxed) {
+            return Err(());
+        }
+        let Some((is_vertical, band_idx, configuration_value_mask)) = self.choose_branch_point()
+        else {
+            solved.store(true, Ordering::Relaxed);
+            return Ok(());
+        };
+        let (first_eliminations, second_eliminations) =
+            self.split_configurations(is_vertical, band_idx, configuration_value_mask);
+        let explore = |mut state: State, eliminations| {
+            state.bands[is_vertical as usize][band_idx].eliminations.0 |= eliminations;
+            state.band_elimination(is_vertical, band_idx, 0)?;
+            state.solve_parallel_inner(solved, depth + 1)?;
+            Ok::<_, ()>(state)
+        };
+        if depth >= MAX_PARALLEL_DEPTH {
+            // The two directions are explored one after the other, but the search still stops as
+            // soon as a solution is found elsewhere
+            *self = explore(self.clone(), first_eliminations)
+                .or_else(|_| explore(self.clone(), second_eliminations))?;
+            return Ok(());
+        }
+
+        let (first, second) = rayon::join(
+            || explore(self.clone(), first_eliminations),
+            || explore(self.clone(), second_eliminations),
+        );
+        *self = first.or(second)?;
+        Ok(())
+    }
+
+    /// Counts the solutions of the sudoku, stopping as soon as `limit` solutions are found.
+    pub fn count_solutions(&mut self, limit: usize) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        let Some((is_vertical, band_idx, configuration_value_mask)) = self.choose_branch_point()
+        else {
+            // Each of the nine digits has exactly one configuration in every band
+            let is_solved = self.bands.iter().flatten().all(|band| {
+                band.configurations
+                    .0
+                    .as_array()
+                    .iter()
+                    .map(|x| x.count_ones())
+                    .sum::<u32>()
+                    == 9
+            });
+            return is_solved as usize;
+        };
+        let (first_eliminations, second_eliminations) =
+            self.split_configurations(is_vertical, band_idx, configuration_value_mask);
+
+        let mut count = 0;
+        let mut state_copy = self.clone();
+        state_copy.bands[is_vertical as usize][band_idx]
+            .eliminations
+            .0 |= first_eliminations;
+        if state_copy
+            .band_elimination(is_vertical, band_idx, 0)
+            .is_ok()
+        {
+            count += state_copy.count_solutions(limit);
+        }
+        if count >= limit {
+            return count;
+        }
+
+        self.bands[is_vertical as usize][band_idx].eliminations.0 |= second_eliminations;
+        if self.band_elimination(is_vertical, band_idx, 0).is_ok() {
+            count += self.count_solutions(limit - count);
+        }
+        count
+    }
 }
 
 #[cfg(test)]
@@ -1595,21 +1805,116 @@ mod tests {
         );
         println!("");
     }
+
+    #[test]
+    fn test_count_solutions() {
+        let count = |values: &str, limit: usize| State::from_values(values).count_solutions(limit);
+        let puzzle =
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        assert_eq!(count(puzzle, 10), 1);
+        // A unique rectangle in r4c6,r4c9,r5c6,r5c9
+        let two_solutions =
+            "53467891267219534819834256785976.42.42685.79.713924856961537284287419635345286179";
+        assert_eq!(count(two_solutions, 10), 2);
+        assert_eq!(count(two_solutions, 1), 1);
+        assert_eq!(count(&".".repeat(81), 10), 10);
+        assert_eq!(count(&format!("55{}", ".".repeat(79)), 10), 0);
+    }
+
+    #[test]
+    fn test_solve_with_trace() {
+        let mut state = State::from_values(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        );
+        let (result, trace) = state.solve_with_trace();
+        assert_eq!(result, Ok(()));
+        assert!(trace.is_empty());
+
+        let puzzle =
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..";
+        let (result, trace) = State::from_values(puzzle).solve_with_trace();
+        assert_eq!(result, Ok(()));
+        assert!(!trace.is_empty());
+        assert!(trace[0].chosen);
+
+        let mut state = State::from_values(puzzle);
+        state.solve().unwrap();
+        assert_eq!(
+            state.to_value_string(),
+            "812753649943682175675491283154237896369845721287169534521974368438526917796318452"
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_solve_parallel() {
+        let puzzle =
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..";
+        let mut sequential = State::from_values(puzzle);
+        sequential.solve().unwrap();
+        let mut parallel = State::from_values(puzzle);
+        parallel.solve_parallel().unwrap();
+        assert_eq!(parallel.to_value_string(), sequential.to_value_string());
+
+        let mut unsolvable = State::from_values(&format!("88{}", &puzzle[2..]));
+        assert_eq!(unsolvable.solve_parallel(), Err(()));
+    }
+
+    #[test]
+    fn test_solve_with_blame() {
+        // r1c1 cannot be filled: r1 contains 1 to 8 and c1 contains 9
+        let mut state =
+            State::from_values(&format!(".12345678{}9{}", ".".repeat(9), ".".repeat(62)));
+        assert_eq!(state.solve_with_blame(), Err(0));
+
+        // r5c7 cannot be filled: r5 contains 1 to 8 and c7 contains 9
+        let mut state = State::from_values(&format!(
+            "......9..{}123456.78{}",
+            ".".repeat(27),
+            ".".repeat(36)
+        ));
+        assert_eq!(state.solve_with_blame(), Err(42));
+
+        let mut state = State::from_values(
+            "6.....3...5..9..8...2..6..98.....7...7..5..4......1..51..3..5...4..2..6...8..7..2",
+        );
+        assert_eq!(state.solve_with_blame(), Ok(()));
+    }
+
+    #[test]
+    fn test_into_sudoku() {
+        let mut state = State::from_values(
+            "6.....3...5..9..8...2..6..98.....7...7..5..4......1..51..3..5...4..2..6...8..7..2",
+        );
+        state.solve().unwrap();
+        assert_eq!(
+            state.into_sudoku().to_candidate_string(),
+            Sudoku::from_values(
+                "689514327457293681312876459835942716971658243264731895126389574743125968598467132"
+            )
+            .to_candidate_string()
+        );
+
+        // Two 1s in the first row
+        let mut state = State::from_values(
+            "11...............................................................................",
+        );
+        assert!(state.solve().is_err());
+        let sudoku = state.into_sudoku();
+        assert!((0..81).any(|cell| sudoku.get_candidates(cell).is_empty()));
+    }
 }
 
 use crate::solver::{SolutionRecorder, SudokuSolver, Technique};
-use crate::sudoku::{CellIndex, CellValue};
+use crate::sudoku::{is_empty_value, CellIndex, CellValue, Sudoku};
 
 pub fn solve_guess(sudoku: &SudokuSolver, recorder: &mut SolutionRecorder) {
     let mut state = State::from_values(&sudoku.sudoku().to_value_string());
     state.solve();
-    for i in 0..81 {
-        let block_index = BlockIndex::from_cell(i as u8);
+    for (i, bits) in state.candidates_grid().into_iter().enumerate() {
         if sudoku.sudoku().get_cell_value(i as CellIndex).is_some() {
             continue;
         }
-        let bits = state.blocks[block_index.block_idx as usize].0.as_array()
-            [block_index.element_idx as usize];
         if bits.count_ones() == 1 {
             let value = bits.trailing_zeros() + 1;
             recorder.add_value_set(
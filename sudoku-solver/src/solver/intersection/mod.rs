@@ -5,17 +5,57 @@ use super::return_in_fast_mode;
 
 // 当 House A 中的一个数字只出现在 House A & House B （A 和 B的交集）中时，这个数字不可能再出现在 House B 中的其他单元格中
 pub fn solve_locked_candidates(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
-    for block in &sudoku.cells_in_blocks {
-        for row in &sudoku.cells_in_rows {
-            check(sudoku, solution, block, row);
+    for block in sudoku.cells_in_blocks() {
+        for row in sudoku.cells_in_rows() {
+            check(sudoku, solution, block, row, Technique::LockedCandidates);
             return_in_fast_mode!(solution);
-            check(sudoku, solution, row, block);
+            check(sudoku, solution, row, block, Technique::LockedCandidates);
             return_in_fast_mode!(solution);
         }
-        for column in &sudoku.cells_in_columns {
-            check(sudoku, solution, block, column);
+        for column in sudoku.cells_in_columns() {
+            check(sudoku, solution, block, column, Technique::LockedCandidates);
             return_in_fast_mode!(solution);
-            check(sudoku, solution, column, block);
+            check(sudoku, solution, column, block, Technique::LockedCandidates);
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+// 与 LockedCandidates 相同，但只搜索一个方向，并以对应的名称记录。
+// Pointing：宫中的数字只出现在一行或一列中，Claiming：行或列中的数字只出现在一个宫中。
+pub fn solve_locked_candidates_pointing(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for block in sudoku.cells_in_blocks() {
+        for line in sudoku
+            .cells_in_rows()
+            .iter()
+            .chain(sudoku.cells_in_columns())
+        {
+            check(
+                sudoku,
+                solution,
+                block,
+                line,
+                Technique::LockedCandidatesPointing,
+            );
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+pub fn solve_locked_candidates_claiming(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for block in sudoku.cells_in_blocks() {
+        for line in sudoku
+            .cells_in_rows()
+            .iter()
+            .chain(sudoku.cells_in_columns())
+        {
+            check(
+                sudoku,
+                solution,
+                line,
+                block,
+                Technique::LockedCandidatesClaiming,
+            );
             return_in_fast_mode!(solution);
         }
     }
@@ -26,6 +66,7 @@ fn check(
     solution: &mut SolutionRecorder,
     house_a: &NamedCellSet,
     house_b: &NamedCellSet,
+    technique: Technique,
 ) {
     let intersection = house_a & house_b;
     if intersection.is_empty() {
@@ -42,18 +83,24 @@ fn check(
                 continue;
             }
             if sudoku.can_fill(cell, value) {
-                solution.add_elimination(
-                    Technique::LockedCandidates,
+                let reason = if technique == Technique::LockedCandidates {
                     format!(
                         "in {}, {} can only be in {} & {}",
                         house_a.name(),
                         value,
                         house_a.name(),
                         house_b.name(),
-                    ),
-                    cell,
-                    value,
-                );
+                    )
+                } else {
+                    format!(
+                        "in {}, {} can only be in {}, so it is not in the rest of {}",
+                        house_a.name(),
+                        value,
+                        house_b.name(),
+                        house_b.name(),
+                    )
+                };
+                solution.add_elimination(technique.clone(), reason, cell, value);
             }
         }
         return_in_fast_mode!(solution);
@@ -0,0 +1,331 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::{comb, CellSet, NamedCellSet};
+
+use itertools::Itertools;
+
+/// A cage of killer sudoku: the values in `cells` are distinct and add up to `sum`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cage {
+    pub cells: CellSet,
+    pub sum: u32,
+}
+
+// 杀手数独的笼子中的数字互不相同，且和为给定的值。
+// Innie：每个 House 中数字之和为 45。若完全位于 House 中的笼子覆盖了 House 中除一个单元格以外的所有单元格，
+// 则该单元格为 45 减去这些笼子之和。
+// Outie：若与 House 相交的笼子覆盖了整个 House，且只多出 House 外的一个单元格，则该单元格为这些笼子之和减去 45。
+// 组合：笼子中未填的单元格需要填入互不相同且和为剩余值的数字，不出现在任何可行的填法中的候选数可以删除。
+pub fn solve_killer_combinations(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    if sudoku.cages().is_empty() {
+        return;
+    }
+
+    for house in sudoku.all_constraints().iter() {
+        if house.size() != 9 {
+            continue;
+        }
+        search_innie(sudoku, solution, house);
+        return_in_fast_mode!(solution);
+        search_outie(sudoku, solution, house);
+        return_in_fast_mode!(solution);
+    }
+
+    for cage in sudoku.cages().iter() {
+        search_combinations(sudoku, solution, cage);
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn cages_to_string(sudoku: &SudokuSolver, cages: &[&Cage]) -> String {
+    cages
+        .iter()
+        .map(|cage| format!("{}({})", sudoku.get_cellset_string(&cage.cells), cage.sum))
+        .join(" ")
+}
+
+fn search_innie(sudoku: &SudokuSolver, solution: &mut SolutionRecorder, house: &NamedCellSet) {
+    let inner_cages = sudoku
+        .cages()
+        .iter()
+        .filter(|cage| cage.cells.is_subset_of(house))
+        .collect_vec();
+    let covered = CellSet::union_multiple(inner_cages.iter().map(|cage| &cage.cells));
+    let remaining = &**house - &covered;
+    if remaining.size() != 1 {
+        return;
+    }
+    let cell = remaining.values()[0];
+    let sum: u32 = inner_cages.iter().map(|cage| cage.sum).sum();
+    let Some(value) = 45u32.checked_sub(sum) else {
+        return;
+    };
+    if (1..=9).contains(&value) && sudoku.can_fill(cell, value as CellValue) {
+        solution.add_value_set(
+            Technique::KillerCombinations,
+            format!(
+                "the cages {} in {} add up to {}, so the remaining cell is 45-{}",
+                cages_to_string(sudoku, &inner_cages),
+                house.name(),
+                sum,
+                sum,
+            ),
+            cell,
+            value as CellValue,
+        );
+    }
+}
+
+fn search_outie(sudoku: &SudokuSolver, solution: &mut SolutionRecorder, house: &NamedCellSet) {
+    let overlapping_cages = sudoku
+        .cages()
+        .iter()
+        .filter(|cage| !(&cage.cells & house).is_empty())
+        .collect_vec();
+    let covered = CellSet::union_multiple(overlapping_cages.iter().map(|cage| &cage.cells));
+    if !house.is_subset_of(&covered) {
+        return;
+    }
+    let outside = &covered - house;
+    if outside.size() != 1 {
+        return;
+    }
+    let cell = outside.values()[0];
+    let sum: u32 = overlapping_cages.iter().map(|cage| cage.sum).sum();
+    let Some(value) = sum.checked_sub(45) else {
+        return;
+    };
+    if (1..=9).contains(&value) && sudoku.can_fill(cell, value as CellValue) {
+        solution.add_value_set(
+            Technique::KillerCombinations,
+            format!(
+                "the cages {} cover {} and add up to {}, so the only cell outside {} is {}-45",
+                cages_to_string(sudoku, &overlapping_cages),
+                house.name(),
+                sum,
+                house.name(),
+                sum,
+            ),
+            cell,
+            value as CellValue,
+        );
+    }
+}
+
+fn search_combinations(sudoku: &SudokuSolver, solution: &mut SolutionRecorder, cage: &Cage) {
+    let cells = (&cage.cells & sudoku.unfilled_cells()).values().to_vec();
+    if cells.is_empty() {
+        return;
+    }
+    let filled_values = cage
+        .cells
+        .iter()
+        .filter_map(|cell| sudoku.cell_value(cell))
+        .collect_vec();
+    let Some(remaining_sum) = cage
+        .sum
+        .checked_sub(filled_values.iter().map(|&value| value as u32).sum())
+    else {
+        return;
+    };
+    let digits = (1..=9)
+        .filter(|value| !filled_values.contains(value))
+        .collect_vec();
+
+    let mut possible = vec![0u16; cells.len()];
+    for combination in sum_combinations(&digits, cells.len(), remaining_sum) {
+        mark_possible_values(sudoku, &cells, combination, &mut possible);
+    }
+
+    for (&cell, &possible) in cells.iter().zip(possible.iter()) {
+        for value in sudoku.candidates(cell).iter() {
+            if possible & (1 << value) == 0 {
+                solution.add_elimination(
+                    Technique::KillerCombinations,
+                    format!(
+                        "the cage {} adds up to {}, and {} is not in any combination that fits the candidates",
+                        sudoku.get_cellset_string(&cage.cells),
+                        cage.sum,
+                        value,
+                    ),
+                    cell,
+                    value,
+                );
+            }
+        }
+    }
+}
+
+/// The bitmasks of the combinations of `size` distinct `digits` that add up to `sum`, where bit `v`
+/// stands for value `v`.
+fn sum_combinations(digits: &[CellValue], size: usize, sum: u32) -> Vec<u16> {
+    if size > digits.len() {
+        return vec![];
+    }
+    let mask = |values: &[CellValue]| values.iter().fold(0u16, |mask, &v| mask | 1 << v);
+    let all = mask(digits);
+    // `comb` only generates small combinations, so large ones are found from their complement
+    if size <= 4 {
+        comb(digits, size)
+            .filter(|values| values.iter().map(|&v| v as u32).sum::<u32>() == sum)
+            .map(|values| mask(&values))
+            .collect()
+    } else {
+        let total: u32 = digits.iter().map(|&v| v as u32).sum();
+        comb(digits, digits.len() - size)
+            .filter(|values| values.iter().map(|&v| v as u32).sum::<u32>() + sum == total)
+            .map(|values| all ^ mask(&values))
+            .collect()
+    }
+}
+
+// 判断 combination 中的数字能否一一填入 cells：forward[i] 为前 i 个单元格可以用掉的数字集合，
+// backward[i] 为第 i 个及之后的单元格可以用掉的数字集合。
+// 单元格 i 可以为 v 当且仅当存在 forward[i] 中的 m 使得 combination - m - v 在 backward[i + 1] 中。
+fn mark_possible_values(
+    sudoku: &SudokuSolver,
+    cells: &[CellIndex],
+    combination: u16,
+    possible: &mut [u16],
+) {
+    let fits = cells
+        .iter()
+        .map(|&cell| {
+            sudoku
+                .candidates(cell)
+                .iter()
+                .fold(0u16, |mask, value| mask | 1 << value)
+                & combination
+        })
+        .collect_vec();
+    let step = |reachable: &[u16], fits: u16| {
+        let mut next = vec![];
+        let mut seen = [false; 1 << 10];
+        for &used in reachable.iter() {
+            for value in 1..=9 {
+                let next_used = used | 1 << value;
+                if fits & !used & (1 << value) != 0 && !seen[next_used as usize] {
+                    seen[next_used as usize] = true;
+                    next.push(next_used);
+                }
+            }
+        }
+        next
+    };
+
+    let mut forward = vec![vec![0u16]];
+    for &fits in fits.iter() {
+        forward.push(step(forward.last().unwrap(), fits));
+    }
+    if !forward[cells.len()].contains(&combination) {
+        return;
+    }
+    let mut backward = vec![vec![]; cells.len() + 1];
+    backward[cells.len()] = vec![0u16];
+    for i in (0..cells.len()).rev() {
+        backward[i] = step(&backward[i + 1], fits[i]);
+    }
+
+    for i in 0..cells.len() {
+        for &used in forward[i].iter() {
+            for value in 1..=9 {
+                if fits[i] & !used & (1 << value) == 0 {
+                    continue;
+                }
+                let rest = combination & !used & !(1 << value);
+                if backward[i + 1].contains(&rest) {
+                    possible[i] |= 1 << value;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{StepKind, Techniques};
+    use crate::sudoku::Sudoku;
+
+    fn load_solver(cages: Vec<Cage>) -> SudokuSolver {
+        let mut solver = SudokuSolver::new(Sudoku::from_values(&".".repeat(81)));
+        solver.initialize_candidates();
+        solver.with_cages(cages)
+    }
+
+    fn cage(cells: &[CellIndex], sum: u32) -> Cage {
+        Cage {
+            cells: cells.iter().copied().collect(),
+            sum,
+        }
+    }
+
+    #[test]
+    fn test_innie() {
+        let mut solver = load_solver(vec![cage(&[0, 1, 2, 3], 10), cage(&[4, 5, 6, 7], 26)]);
+        // An empty grid has no singles, intersections or subsets
+        let basic_techniques = Techniques::from(
+            [
+                Technique::FullHouse,
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+                Technique::LockedCandidates,
+                Technique::NakedSubset,
+                Technique::HiddenSubset,
+            ]
+            .into_iter(),
+        );
+        assert!(solver.solve_one_step(&basic_techniques).is_none());
+
+        let techniques = Techniques::from([Technique::KillerCombinations].into_iter());
+        let step = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(step.steps.len(), 1);
+        assert_eq!(step.steps[0].kind, StepKind::ValueSet);
+        assert_eq!((step.steps[0].cell_index, step.steps[0].value), (8, 9));
+        solver.apply_step(&step);
+
+        // 10 = 1+2+3+4 is the only combination of 4 distinct values
+        let step = solver.solve_one_step(&techniques).unwrap();
+        assert!(step
+            .steps
+            .iter()
+            .all(|step| step.kind == StepKind::CandidateEliminated && step.cell_index < 4));
+        solver.apply_step(&step);
+        for cell in 0..4 {
+            assert_eq!(solver.cell_candidates(cell).collect_vec(), vec![1, 2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn test_outie() {
+        let solver = load_solver(vec![cage(&[0, 1, 2, 3, 9], 17), cage(&[4, 5, 6, 7, 8], 35)]);
+        let mut solution = SolutionRecorder::new();
+        solve_killer_combinations(&solver, &mut solution);
+        assert_eq!(solution.steps.len(), 1);
+        assert_eq!(solution.steps[0].kind, StepKind::ValueSet);
+        assert_eq!(
+            (solution.steps[0].cell_index, solution.steps[0].value),
+            (9, 7)
+        );
+    }
+
+    #[test]
+    fn test_sum_combinations() {
+        let digits = (1..=9).collect_vec();
+        assert_eq!(sum_combinations(&digits, 2, 3), vec![0b110]);
+        assert_eq!(sum_combinations(&digits, 3, 24), vec![0b1110000000]);
+        assert_eq!(sum_combinations(&digits, 8, 44), vec![0b1111111100]);
+        assert_eq!(sum_combinations(&digits, 5, 15), vec![0b111110]);
+        assert_eq!(sum_combinations(&digits, 2, 17).len(), 1);
+        assert_eq!(sum_combinations(&digits, 2, 10).len(), 4);
+        assert!(sum_combinations(&digits[..3], 4, 10).is_empty());
+    }
+
+    #[test]
+    fn test_no_cages() {
+        let solver = load_solver(vec![]);
+        let mut solution = SolutionRecorder::new();
+        solve_killer_combinations(&solver, &mut solution);
+        assert!(solution.steps.is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, StrongLink, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+
+use itertools::Itertools;
+
+type Candidate = (CellIndex, CellValue);
+
+// 3D Medusa：将每个候选数 (cell, value) 视为节点，bivalue 单元格与 bilocation 构成的 strong link 连接的节点组成一个 cluster。
+// strong link 的两端恰好一个为真，因此可以对每个 cluster 二染色，两种颜色中恰好有一种全部为真。
+// 首先检查某一种颜色直接导致的矛盾：
+//   1. 同一单元格中有两个该颜色的候选数；
+//   2. 同一 House 中同一数字有两个该颜色的候选数；
+//   3. 某个单元格中没有该颜色的候选数，而其所有候选数都能看到同数字的该颜色候选数，该单元格将没有候选数。
+// 出现矛盾的颜色为假，可以删除该颜色的所有候选数。
+// 然后删除同时能看到两种颜色的未染色候选数，这里候选数能看到同一单元格中的其他候选数，以及同一 House 中同数字的候选数：
+//   1. 同一单元格中有两种颜色的候选数时，该单元格的其他候选数；
+//   2. 同一 House 中某个数字有两种颜色的候选数时，该 House 中该数字的其他候选数；
+//   3. 单元格中有一种颜色的候选数，且能看到另一种颜色的同数字候选数时，该单元格中的该数字。
+pub fn solve_3d_medusa(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let clusters = color_clusters(sudoku);
+    for cluster in clusters.iter() {
+        solve_contradiction(sudoku, solution, cluster, Technique::Medusa3D);
+        return_in_fast_mode!(solution);
+    }
+    for cluster in clusters.iter() {
+        solve_trap(sudoku, solution, cluster);
+        return_in_fast_mode!(solution);
+    }
+}
+
+// 只检查矛盾的 3D Medusa，作为完整规则之前的快速检查
+pub fn solve_3d_medusa_contradiction(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for cluster in color_clusters(sudoku).iter() {
+        solve_contradiction(sudoku, solution, cluster, Technique::ThreeDimensionalMedusa);
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn solve_contradiction(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    cluster: &[Vec<Candidate>; 2],
+    technique: Technique,
+) {
+    for candidates in cluster.iter() {
+        let Some(contradiction) = find_contradiction(sudoku, candidates) else {
+            continue;
+        };
+        let reason = format!(
+            "{} cannot all be true as {}",
+            candidates_to_string(sudoku, candidates),
+            contradiction
+        );
+        for &(cell, value) in candidates.iter() {
+            solution.add_elimination(technique.clone(), reason.clone(), cell, value);
+        }
+        // 两种颜色不可能同时为假
+        return;
+    }
+}
+
+fn solve_trap(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    cluster: &[Vec<Candidate>; 2],
+) {
+    let sees = |(cell1, value1): Candidate, (cell2, value2): Candidate| {
+        if cell1 == cell2 {
+            value1 != value2
+        } else {
+            value1 == value2 && sudoku.house_union_of_cell(cell1).has(cell2)
+        }
+    };
+    for cell in sudoku.unfilled_cells().iter() {
+        for value in sudoku.candidates(cell).iter() {
+            let candidate = (cell, value);
+            if cluster
+                .iter()
+                .any(|candidates| candidates.contains(&candidate))
+            {
+                continue;
+            }
+            let Some(&seen1) = cluster[0].iter().find(|&&other| sees(candidate, other)) else {
+                continue;
+            };
+            let Some(&seen2) = cluster[1].iter().find(|&&other| sees(candidate, other)) else {
+                continue;
+            };
+            solution.add_elimination(
+                Technique::Medusa3D,
+                format!(
+                    "coloring {} and {}, {} sees both colors in {} and {}",
+                    candidates_to_string(sudoku, &cluster[0]),
+                    candidates_to_string(sudoku, &cluster[1]),
+                    candidate_to_string(sudoku, candidate),
+                    candidate_to_string(sudoku, seen1),
+                    candidate_to_string(sudoku, seen2),
+                ),
+                cell,
+                value,
+            );
+        }
+    }
+}
+
+fn candidate_to_string(sudoku: &SudokuSolver, (cell, value): Candidate) -> String {
+    format!("{}={}", sudoku.get_cell_name(cell), value)
+}
+
+fn candidates_to_string(sudoku: &SudokuSolver, candidates: &[Candidate]) -> String {
+    candidates
+        .iter()
+        .map(|&candidate| candidate_to_string(sudoku, candidate))
+        .join(",")
+}
+
+/// Colors the candidates connected by strong links with two alternating colors. Returns the
+/// candidates of both colors for each cluster, exactly one color of which is true.
+pub(crate) fn color_clusters(sudoku: &SudokuSolver) -> Vec<[Vec<Candidate>; 2]> {
+    let node = |(cell, value): Candidate| cell as usize * 9 + value as usize - 1;
+    let mut links = vec![vec![]; 81 * 9];
+    for link in sudoku.strong_links() {
+        let (candidate1, candidate2) = match link {
+            StrongLink::Bivalue {
+                cell,
+                values: (value1, value2),
+            } => ((cell, value1), (cell, value2)),
+            StrongLink::Bilocation {
+                value,
+                cells: (cell1, cell2),
+                ..
+            } => ((cell1, value), (cell2, value)),
+        };
+        links[node(candidate1)].push(candidate2);
+        links[node(candidate2)].push(candidate1);
+    }
+
+    let mut colors = vec![None; 81 * 9];
+    let mut clusters = vec![];
+    for cell in sudoku.unfilled_cells().iter() {
+        for value in sudoku.candidates(cell).iter() {
+            let start = (cell, value);
+            if colors[node(start)].is_some() || links[node(start)].is_empty() {
+                continue;
+            }
+            let mut cluster = [vec![], vec![]];
+            let mut is_consistent = true;
+            colors[node(start)] = Some(0);
+            let mut stack = vec![start];
+            while let Some(candidate) = stack.pop() {
+                let color = colors[node(candidate)].unwrap();
+                cluster[color].push(candidate);
+                for &next in links[node(candidate)].iter() {
+                    match colors[node(next)] {
+                        None => {
+                            colors[node(next)] = Some(1 - color);
+                            stack.push(next);
+                        }
+                        Some(next_color) if next_color == color => is_consistent = false,
+                        _ => {}
+                    }
+                }
+            }
+            // 奇数长度的环说明数独无解，不对这样的 cluster 进行推导
+            if is_consistent {
+                cluster.iter_mut().for_each(|candidates| candidates.sort());
+                clusters.push(cluster);
+            }
+        }
+    }
+    clusters
+}
+
+fn find_contradiction(sudoku: &SudokuSolver, candidates: &[Candidate]) -> Option<String> {
+    for (&(cell1, value1), &(cell2, value2)) in candidates.iter().tuple_combinations() {
+        if cell1 == cell2 {
+            return Some(format!(
+                "{} would have both {} and {}",
+                sudoku.get_cell_name(cell1),
+                value1,
+                value2
+            ));
+        }
+        if value1 != value2 {
+            continue;
+        }
+        if let Some(house) = sudoku
+            .constraints_of_cell(cell1)
+            .iter()
+            .find(|house| house.has(cell2))
+        {
+            return Some(format!("{} would have two {}", house.name(), value1));
+        }
+    }
+
+    for cell in sudoku.unfilled_cells().iter() {
+        if candidates
+            .iter()
+            .any(|&(colored_cell, _)| colored_cell == cell)
+        {
+            continue;
+        }
+        let is_emptied = sudoku.candidates(cell).iter().all(|value| {
+            candidates.iter().any(|&(colored_cell, colored_value)| {
+                colored_value == value && sudoku.house_union_of_cell(cell).has(colored_cell)
+            })
+        });
+        if is_emptied {
+            return Some(format!(
+                "{} would have no candidate",
+                sudoku.get_cell_name(cell)
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    fn first_step(candidates: &str) -> Option<String> {
+        first_step_with(solve_3d_medusa, candidates)
+    }
+
+    fn first_step_with(
+        solve: fn(&SudokuSolver, &mut SolutionRecorder),
+        candidates: &str,
+    ) -> Option<String> {
+        let solver = SudokuSolver::new(Sudoku::from_candidates(candidates));
+        let mut solution = SolutionRecorder::new();
+        solve(&solver, &mut solution);
+        solution
+            .to_string(solver.sudoku())
+            .lines()
+            .next()
+            .map(|line| line.to_string())
+    }
+
+    const SAME_COLOR_TWICE_IN_HOUSE: &str = "
+        6 1 25 7 3 9 8 4 25
+        89 459 3 468 2468 2468 259 1 7
+        7 49 28 148 5 1248 29 3 6
+        1 59 4 3 28 28 7 6 59
+        2 6 7 149 14 145 345 8 3459
+        89 3 58 469 7 456 1 2 459
+        3 28 6 5 9 148 24 7 1248
+        5 28 1 468 468 7 234 9 2348
+        4 7 9 2 18 3 6 5 18
+        ";
+
+    const SEES_BOTH_COLORS: &str = "
+        8 19 5 6 3 2 7 4 19
+        4 19 7 58 58 19 26 3 26
+        6 3 2 47 47 19 19 5 8
+        23 7 8 1 2456 456 39 26 359
+        9 5 4 3 26 8 26 1 7
+        23 6 1 9 257 57 4 8 35
+        1 8 6 27 9 3 5 27 4
+        7 4 3 258 568 56 18 9 126
+        5 2 9 478 1 467 38 67 36
+        ";
+
+    #[test]
+    fn test_same_color_twice_in_house() {
+        let step = first_step(SAME_COLOR_TWICE_IN_HOUSE);
+        assert_eq!(
+            step.as_deref(),
+            Some(
+                "[Medusa3D] r1c3=2,r1c9=5,r2c1=9,r2c2=5,r3c3=8,r4c2=9,r4c9=5,\
+                 r5c7=5,r6c1=8,r6c3=5 cannot all be true as c9 would have two 5 => r1c3<>2"
+            )
+        );
+    }
+
+    #[test]
+    fn test_cell_emptied_by_color() {
+        let step = first_step(
+            "
+            2 378 1389 5 38 18 6 179 4
+            79 3578 13589 148 348 6 2 1579 157
+            4 6 15 2 7 9 8 15 3
+            1 38 38 7 5 2 4 6 9
+            5 4 2 69 69 38 7 138 18
+            6 9 7 48 1 348 5 238 28
+            79 57 6 149 2 14 3 458 58
+            3 1 4 68 68 5 9 27 27
+            8 2 59 3 49 7 1 45 6
+            ",
+        );
+        assert!(step
+            .unwrap()
+            .contains("cannot all be true as r6c4 would have no candidate"));
+    }
+
+    #[test]
+    fn test_sees_both_colors() {
+        let step = first_step(SEES_BOTH_COLORS);
+        assert!(step
+            .unwrap()
+            .ends_with("r4c5=5 sees both colors in r4c5=4 and r6c6=5 => r4c5<>5"));
+    }
+
+    #[test]
+    fn test_contradiction_only() {
+        let step = first_step_with(solve_3d_medusa_contradiction, SAME_COLOR_TWICE_IN_HOUSE);
+        assert!(step
+            .unwrap()
+            .starts_with("[ThreeDimensionalMedusa] r1c3=2,r1c9=5,r2c1=9"));
+        // the fast mode does not look for candidates seeing both colors
+        assert_eq!(
+            first_step_with(solve_3d_medusa_contradiction, SEES_BOTH_COLORS),
+            None
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::CellSet;
+
+use std::collections::VecDeque;
+
+use itertools::Itertools;
+
+// Remote Pairs：候选数恰好为同一对数字 {a,b} 的单元格，相邻两个单元格互相可见，构成一条链。
+// 链上的单元格交替为 a 与 b，因此相隔奇数个 link 的两个单元格一个为 a、一个为 b。
+// 能同时看到这样两个单元格的单元格中不可能出现 a 与 b。
+// 相隔一个 link 的两个单元格即为 naked pair，因此只考虑相隔至少三个 link 的单元格。
+pub fn solve_remote_pairs(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let bivalue_cells: Vec<CellIndex> = sudoku
+        .unfilled_cells()
+        .iter()
+        .filter(|&cell| sudoku.candidates(cell).size() == 2)
+        .collect();
+    if bivalue_cells.len() < 4 {
+        return;
+    }
+
+    for (a, b) in (1..=9).tuple_combinations() {
+        let pair: [CellValue; 2] = [a, b];
+        let chain_cells: CellSet = bivalue_cells
+            .iter()
+            .copied()
+            .filter(|&cell| pair.iter().all(|&value| sudoku.can_fill(cell, value)))
+            .collect();
+        if chain_cells.size() < 4 {
+            continue;
+        }
+        for start in chain_cells.iter() {
+            search_from(sudoku, solution, &chain_cells, pair, start);
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+fn search_from(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    chain_cells: &CellSet,
+    pair: [CellValue; 2],
+    start: CellIndex,
+) {
+    // cell -> (previous cell on the chain, number of links from start)
+    let mut previous = vec![None; 81];
+    let mut queue = VecDeque::new();
+    previous[start as usize] = Some((start, 0));
+    queue.push_back(start);
+
+    while let Some(cell) = queue.pop_front() {
+        let links = previous[cell as usize].unwrap().1;
+        for next in (chain_cells & sudoku.house_union_of_cell(cell)).iter() {
+            if previous[next as usize].is_some() {
+                continue;
+            }
+            previous[next as usize] = Some((cell, links + 1));
+            queue.push_back(next);
+
+            // 每一对首尾只在 start < end 时记录一次
+            if next < start || links + 1 < 3 || (links + 1) % 2 == 0 {
+                continue;
+            }
+            let mut chain = vec![next];
+            while *chain.last().unwrap() != start {
+                chain.push(previous[*chain.last().unwrap() as usize].unwrap().0);
+            }
+            chain.reverse();
+            record_eliminations(sudoku, solution, &chain, pair);
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+fn record_eliminations(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    chain: &[CellIndex],
+    pair: [CellValue; 2],
+) {
+    let start = chain[0];
+    let end = chain[chain.len() - 1];
+    let mut eliminated = sudoku.house_union_of_cell(start) & sudoku.house_union_of_cell(end);
+    eliminated -= &chain.iter().copied().collect::<CellSet>();
+
+    let reason = format!(
+        "remote pair chain of {}: {}",
+        pair.iter().join(","),
+        chain
+            .iter()
+            .map(|&cell| sudoku.get_cell_name(cell))
+            .join("-"),
+    );
+    for cell in eliminated.iter() {
+        for &value in pair.iter() {
+            if sudoku.can_fill(cell, value) {
+                solution.add_elimination(Technique::RemotePairs, reason.clone(), cell, value);
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::solver::{SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::CellIndex;
 
 use super::return_in_fast_mode;
 
@@ -23,10 +24,14 @@ pub fn solve_full_house(sudoku: &SudokuSolver, solution: &mut SolutionRecorder)
     }
 }
 
+// 启用 FullHouse 时，House 中唯一未填的单元格由 FullHouse 给出，这里跳过以免重复解释
 pub fn solve_naked_single(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
-    for house in sudoku.all_constraints.iter() {
+    for house in sudoku.all_constraints().iter() {
         for cell in house.iter() {
             if sudoku.candidates(cell).size() == 1 {
+                if solution.full_house_enabled() && is_full_house(sudoku, cell) {
+                    continue;
+                }
                 let value = sudoku.candidates(cell).iter().next().unwrap();
                 solution.add_value_set(
                     Technique::NakedSingle,
@@ -44,8 +49,15 @@ pub fn solve_naked_single(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
     }
 }
 
+fn is_full_house(sudoku: &SudokuSolver, cell: CellIndex) -> bool {
+    sudoku
+        .constraints_of_cell(cell)
+        .iter()
+        .any(|house| (&**house & sudoku.unfilled_cells()).size() == 1)
+}
+
 pub fn solve_hidden_single(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
-    for house in sudoku.all_constraints.iter() {
+    for house in sudoku.all_constraints().iter() {
         if (sudoku.unfilled_cells() & house).is_empty() {
             continue;
         }
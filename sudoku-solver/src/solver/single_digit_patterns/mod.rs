@@ -1,4 +1,5 @@
 mod rectangle_elimination;
+mod simple_coloring;
 mod skyscraper;
 mod two_string_kite;
 
@@ -24,3 +25,10 @@ pub fn solve_rectangle_elimination(sudoku: &SudokuSolver, solution: &mut Solutio
         return_in_fast_mode!(solution);
     }
 }
+
+pub fn solve_simple_coloring(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for value in 1..=9 {
+        simple_coloring::search_simple_coloring(sudoku, solution, value);
+        return_in_fast_mode!(solution);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::solver::return_in_fast_mode;
+use crate::solver::{SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::CellSet;
+
+use std::collections::VecDeque;
+
+// 对单个数字，有且仅有两个可能位置的 House 中的两个单元格构成 conjugate pair，恰好一个为真。
+// 将 conjugate pair 构成的图的每个连通分量染成两种颜色，则恰好一种颜色的所有单元格为真。
+// Color wrap：若同一种颜色的两个单元格位于同一个 House，则该颜色为假，可以删除该颜色的所有单元格中的该数字。
+// Color trap：若未染色的单元格能同时看到两种颜色的单元格，则该单元格中不可能出现该数字。
+pub fn search_simple_coloring(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    value: CellValue,
+) {
+    let links = conjugate_pairs(sudoku, value);
+    let mut colored = CellSet::new();
+    for &(start, _) in links.iter() {
+        if colored.has(start) {
+            continue;
+        }
+        let colors = color_component(&links, start);
+        colored |= &colors[0];
+        colored |= &colors[1];
+        // 只有一个 conjugate pair 时即为 locked candidates
+        if colors[0].size() + colors[1].size() <= 2 {
+            continue;
+        }
+
+        search_color_wrap(sudoku, solution, value, &colors);
+        return_in_fast_mode!(solution);
+        search_color_trap(sudoku, solution, value, &colors);
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn conjugate_pairs(sudoku: &SudokuSolver, value: CellValue) -> Vec<(CellIndex, CellIndex)> {
+    let mut links = vec![];
+    for (_, (_, _, cell1), (_, _, cell2)) in sudoku
+        .rows_with_only_two_possible_places(value)
+        .iter()
+        .chain(sudoku.cols_with_only_two_possible_places(value))
+    {
+        links.push((*cell1, *cell2));
+    }
+    for block in sudoku.candidate_cells_in_blocks(value) {
+        if let [cell1, cell2] = block.values()[..] {
+            links.push((cell1, cell2));
+        }
+    }
+    links
+}
+
+fn color_component(links: &[(CellIndex, CellIndex)], start: CellIndex) -> [CellSet; 2] {
+    let mut colors = [CellSet::new(), CellSet::new()];
+    let mut queue = VecDeque::new();
+    colors[0].add(start);
+    queue.push_back((start, 0));
+    while let Some((cell, color)) = queue.pop_front() {
+        for &(cell1, cell2) in links.iter() {
+            let next = if cell1 == cell {
+                cell2
+            } else if cell2 == cell {
+                cell1
+            } else {
+                continue;
+            };
+            if colors[0].has(next) || colors[1].has(next) {
+                continue;
+            }
+            colors[1 - color].add(next);
+            queue.push_back((next, 1 - color));
+        }
+    }
+    colors
+}
+
+fn colors_to_string(sudoku: &SudokuSolver, colors: &[CellSet; 2]) -> String {
+    format!(
+        "coloring {} and {}",
+        sudoku.get_cellset_string(&colors[0]),
+        sudoku.get_cellset_string(&colors[1]),
+    )
+}
+
+fn search_color_wrap(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    value: CellValue,
+    colors: &[CellSet; 2],
+) {
+    for color in colors.iter() {
+        for (i, cell1) in color.iter().enumerate() {
+            for cell2 in color.iter().skip(i + 1) {
+                let Some(house) = sudoku
+                    .all_constraints()
+                    .iter()
+                    .find(|house| house.has(cell1) && house.has(cell2))
+                else {
+                    continue;
+                };
+                let reason = format!(
+                    "for {}, {}, {} and {} of the same color are both in {}, so {} are false",
+                    value,
+                    colors_to_string(sudoku, colors),
+                    sudoku.get_cell_name(cell1),
+                    sudoku.get_cell_name(cell2),
+                    house.name(),
+                    sudoku.get_cellset_string(color),
+                );
+                for cell in color.iter() {
+                    solution.add_elimination(
+                        Technique::SimpleColoring,
+                        reason.clone(),
+                        cell,
+                        value,
+                    );
+                }
+                return;
+            }
+        }
+    }
+}
+
+fn search_color_trap(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    value: CellValue,
+    colors: &[CellSet; 2],
+) {
+    let mut uncolored = sudoku.possible_cells(value) - &colors[0];
+    uncolored -= &colors[1];
+    for cell in uncolored.iter() {
+        let seen = sudoku.house_union_of_cell(cell);
+        let Some(cell1) = colors[0].iter().find(|&c| seen.has(c)) else {
+            continue;
+        };
+        let Some(cell2) = colors[1].iter().find(|&c| seen.has(c)) else {
+            continue;
+        };
+        solution.add_elimination(
+            Technique::SimpleColoring,
+            format!(
+                "for {}, {}, {} sees both colors in {} and {}",
+                value,
+                colors_to_string(sudoku, colors),
+                sudoku.get_cell_name(cell),
+                sudoku.get_cell_name(cell1),
+                sudoku.get_cell_name(cell2),
+            ),
+            cell,
+            value,
+        );
+        return_in_fast_mode!(solution);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Techniques;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_simple_coloring() {
+        let techniques = Techniques::from(["simple_coloring"].into_iter());
+
+        // Color trap: the component is linked by r2, r3, b2 and c5
+        let sudoku = Sudoku::from_candidates(
+            "
+            457 6 2 3 457 457 9 1 8
+            3 45 15 9 145 8 7 2 6
+            17 8 9 2 6 17 3 4 5
+            1458 7 1568 46 149 149 1458 3 2
+            2 45 1568 467 3 147 1458 58 9
+            14 9 3 5 8 2 14 6 7
+            69 2 4 8 59 3 56 7 1
+            58 1 58 47 47 6 2 9 3
+            69 3 7 1 2 59 568 58 4
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            solution.to_string(solver.sudoku()).trim(),
+            "[SimpleColoring] for 1, coloring r2c3,r3c6,r4c5 and r2c5,r3c1, r4c1 sees both colors in r4c5 and r3c1 => r4c1<>1"
+        );
+
+        // Color wrap: the component is linked by r1, r3, r7, b1 and c5
+        let sudoku = Sudoku::from_candidates(
+            "
+            58 9 6 4 578 78 2 3 1
+            7 1 4 9 2 3 5 6 8
+            2 3 58 58 1 6 7 9 4
+            6 2 3 18 78 178 9 4 5
+            4 8 9 3 6 5 1 7 2
+            1 5 7 2 4 9 3 8 6
+            9 7 158 6 58 18 4 2 3
+            35 6 2 157 39 4 8 15 79
+            358 4 158 1578 39 2 6 15 79
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        let reason = "for 5, coloring r1c1,r3c4,r7c5 and r1c5,r3c3,r7c3, r3c3 and r7c3 of the same color are both in c3, so r1c5,r3c3,r7c3 are false";
+        assert_eq!(
+            solution.to_string(solver.sudoku()).trim(),
+            format!(
+                "[SimpleColoring] {} => r1c5<>5\n[SimpleColoring] {} => r3c3<>5\n[SimpleColoring] {} => r7c3<>5",
+                reason, reason, reason
+            )
+        );
+    }
+}
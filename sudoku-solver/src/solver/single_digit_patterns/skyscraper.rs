@@ -73,11 +73,13 @@ fn search_skyscraper_inner(
                 solution.add_elimination(
                     Technique::Skyscraper,
                     format!(
-                        "there are only two possible cells to place {} in {} and {}, and two of those cells shares {}",
+                        "there are only two possible cells to place {} in {} and {}, and two of those cells shares {}, so the towers at {} and {} see this cell",
                         value,
                         row_a.name(),
                         row_b.name(),
-                        common_cols_name
+                        common_cols_name,
+                        sudoku.get_cell_name(cell_1),
+                        sudoku.get_cell_name(cell_2),
                     ),
                     cell, value);
             }
@@ -10,7 +10,7 @@ pub fn search_two_string_kite(
     let rows = sudoku.rows_with_only_two_possible_places(value);
     let cols = sudoku.cols_with_only_two_possible_places(value);
 
-    if rows.is_empty() || cols.is_empty() {
+    if rows.is_empty() && cols.is_empty() {
         return;
     }
 
@@ -49,4 +49,48 @@ pub fn search_two_string_kite(
             }
         }
     }
+
+    // 宫中只有两个位置的 conjugate pair 也可以作为其中一根 string：
+    // 若行或列的一端与宫的一端位于同一行或列，则两根 string 的另一端至少有一个为真
+    for block in sudoku
+        .candidate_cells_in_blocks(value)
+        .iter()
+        .filter(|block| block.size() == 2)
+    {
+        let block_cells = block.values();
+        for (line, (_, _, cell_a), (_, _, cell_b)) in rows.iter().chain(cols) {
+            if block.has(*cell_a) || block.has(*cell_b) {
+                continue;
+            }
+            let line_cells = [*cell_a, *cell_b];
+            for (i, j) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                let (line_base, line_end) = (line_cells[i], line_cells[1 - i]);
+                let (block_base, block_end) = (block_cells[j], block_cells[1 - j]);
+                if !sudoku.house_union_of_cell(line_base).has(block_base) {
+                    continue;
+                }
+                let mut eliminated =
+                    sudoku.possible_cells(value) & sudoku.house_union_of_cell(line_end);
+                eliminated &= sudoku.house_union_of_cell(block_end);
+                eliminated.remove(line_base);
+                eliminated.remove(block_base);
+                for cell in eliminated.iter() {
+                    solution.add_elimination(
+                        Technique::TwoStringKite,
+                        format!(
+                            "for {}, there are only two places in {} and {}, connected by {} and {}",
+                            value,
+                            line.name(),
+                            block.name(),
+                            sudoku.get_cell_name(line_base),
+                            sudoku.get_cell_name(block_base),
+                        ),
+                        cell,
+                        value,
+                    );
+                }
+                return_in_fast_mode!(solution);
+            }
+        }
+    }
 }
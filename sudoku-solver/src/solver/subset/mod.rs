@@ -1,12 +1,14 @@
 use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
 use crate::utils::{comb, CellSet, ValueSet};
 
 use arrayvec::ArrayVec;
 use itertools::Itertools;
+use rustc_hash::FxHashSet;
 
 // 在一个 House 中，若任意 n 个数字只可能出现在相同 n 个（或更少）单元格中，则这 n 个单元格中不可能出现其他数字
 pub fn solve_hidden_subset(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
-    for house in sudoku.all_constraints.iter() {
+    for house in sudoku.all_constraints().iter() {
         let mut possible_cells_in_houses = vec![];
         for value in 1..=9 {
             let possible_cells_in_house =
@@ -60,7 +62,7 @@ pub fn solve_hidden_subset(sudoku: &SudokuSolver, solution: &mut SolutionRecorde
 
 // 当一个 House 中的 n 个单元格只包含相同的 n 个（或更少）数字时，这 n 个数字不可能出现在这个 House 中的其他单元格中
 pub fn solve_naked_subset(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
-    for house in sudoku.all_constraints.iter() {
+    for house in sudoku.all_constraints().iter() {
         for size in 2..=4 {
             for subset in house
                 .iter()
@@ -109,3 +111,233 @@ pub fn solve_naked_subset(sudoku: &SudokuSolver, solution: &mut SolutionRecorder
         }
     }
 }
+
+// naked subset 与 hidden subset 互为对偶：若一个 House 中有 k 个未填单元格，其中 n 个单元格只包含 n 个数字，
+// 则剩下的 k-n 个单元格恰好包含剩下的 k-n 个数字，两者删除的候选数完全相同。
+// 因此只需搜索一次，并以规模较小的一种解释记录。
+pub fn solve_locked_sets(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let mut eliminated = FxHashSet::default();
+    for house in sudoku.all_constraints().iter() {
+        let unfilled_cells = ArrayVec::<_, 9>::from_iter(
+            house
+                .iter()
+                .filter(|&cell| sudoku.cell_value(cell).is_none()),
+        );
+        let unfilled_count = unfilled_cells.len();
+        let values_in_house =
+            ValueSet::union_multiple(unfilled_cells.iter().map(|&cell| sudoku.candidates(cell)));
+
+        for size in 1..unfilled_count {
+            let is_naked_subset = (2..=4).contains(&size);
+            let is_hidden_subset = (2..=4).contains(&(unfilled_count - size));
+            if !is_naked_subset && !is_hidden_subset {
+                continue;
+            }
+            for subset in unfilled_cells
+                .iter()
+                .copied()
+                .filter(|&cell| sudoku.candidates(cell).size() <= size)
+                .combinations(size)
+            {
+                let value_union = ValueSet::from_iter(
+                    subset
+                        .iter()
+                        .flat_map(|&cell| sudoku.candidates(cell).iter()),
+                );
+                if value_union.size() > size {
+                    continue;
+                }
+                let cells_in_subset = CellSet::from_iter(subset);
+                let other_cells = CellSet::from_iter(
+                    unfilled_cells
+                        .iter()
+                        .copied()
+                        .filter(|&cell| !cells_in_subset.has(cell)),
+                );
+
+                let (technique, reason) =
+                    if is_naked_subset && (!is_hidden_subset || size <= unfilled_count - size) {
+                        (
+                            Technique::NakedSubset,
+                            format!(
+                                "in {}, {} only contains {}",
+                                house.name(),
+                                sudoku.get_cellset_string(&cells_in_subset),
+                                value_union.iter().join(","),
+                            ),
+                        )
+                    } else {
+                        (
+                            Technique::HiddenSubset,
+                            format!(
+                                "in {}, {} only appears in {}",
+                                house.name(),
+                                (&values_in_house - &value_union).iter().join(","),
+                                sudoku.get_cellset_string(&other_cells),
+                            ),
+                        )
+                    };
+
+                for cell in other_cells.iter() {
+                    for value in value_union.iter() {
+                        if sudoku.can_fill(cell, value) && eliminated.insert((cell, value)) {
+                            solution.add_elimination(
+                                technique.clone(),
+                                reason.clone(),
+                                cell,
+                                value,
+                            );
+                        }
+                    }
+                }
+                return_in_fast_mode!(solution);
+            }
+        }
+    }
+}
+
+// 计数约束：在一个 House 中，任意 n 个数字的可能位置的并集至少有 n 个单元格。
+// 若恰好为 n 个，则这 n 个单元格被这 n 个数字占满，其中不可能出现其他数字。
+// 与 hidden subset 的结论相同，但用 House 内 9 位的位置掩码做计数，可以作为快速的预筛选。
+pub fn solve_remaining_count(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for house in sudoku.all_constraints().iter() {
+        let cells = house.values();
+        let mut position_masks = [0u16; 10];
+        for (pos, &cell) in cells.iter().enumerate() {
+            for value in sudoku.candidates(cell).iter() {
+                position_masks[value as usize] |= 1 << pos;
+            }
+        }
+        let values = ArrayVec::<CellValue, 9>::from_iter(
+            (1..=9).filter(|&value| (1..=4).contains(&position_masks[value as usize].count_ones())),
+        );
+
+        for size in 2..=4 {
+            for subset in comb(&values, size) {
+                let union = subset
+                    .iter()
+                    .fold(0, |union, &value| union | position_masks[value as usize]);
+                if union.count_ones() as usize > size {
+                    continue;
+                }
+
+                let values_in_subset = ValueSet::from_iter(subset.iter().copied());
+                let cells_in_subset = CellSet::from_iter(
+                    (0..cells.len())
+                        .filter(|pos| union & (1 << pos) != 0)
+                        .map(|pos| cells[pos]),
+                );
+                for cell in cells_in_subset.iter() {
+                    for value in (sudoku.candidates(cell) - &values_in_subset).iter() {
+                        solution.add_elimination(
+                            Technique::RemainingCount,
+                            format!(
+                                "in {}, {} values {} can only be in {} cells {}",
+                                house.name(),
+                                size,
+                                values_in_subset.iter().join(","),
+                                size,
+                                sudoku.get_cellset_string(&cells_in_subset),
+                            ),
+                            cell,
+                            value,
+                        );
+                    }
+                }
+                return_in_fast_mode!(solution);
+            }
+        }
+    }
+}
+
+// distributed disjoint subset：n 个不全在同一 House 中的单元格只包含 n 个数字，且对每个数字，包含它的单元格都在同一 House 中。
+// 每个数字在这些单元格中至多出现一次，而 n 个单元格需要 n 个数字，因此每个数字恰好出现一次。
+// 能看到所有包含某个数字的单元格的其他单元格中不可能出现该数字。
+pub fn solve_distributed_disjoint_subsets(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let cells = sudoku
+        .unfilled_cells()
+        .iter()
+        .filter(|&cell| (2..=4).contains(&sudoku.candidates(cell).size()))
+        .collect_vec();
+    search_distributed_disjoint_subsets(sudoku, solution, &cells, 0, &mut vec![], &ValueSet::new());
+}
+
+fn search_distributed_disjoint_subsets(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    cells: &[CellIndex],
+    start: usize,
+    subset: &mut Vec<CellIndex>,
+    values: &ValueSet,
+) {
+    if subset.len() >= 3 && subset.len() == values.size() {
+        check_distributed_disjoint_subset(sudoku, solution, subset, values);
+        return;
+    }
+    if subset.len() == 4 {
+        return;
+    }
+    for (i, &cell) in cells.iter().enumerate().skip(start) {
+        let union = values | sudoku.candidates(cell);
+        if union.size() > 4 {
+            continue;
+        }
+        subset.push(cell);
+        search_distributed_disjoint_subsets(sudoku, solution, cells, i + 1, subset, &union);
+        subset.pop();
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn check_distributed_disjoint_subset(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    subset: &[CellIndex],
+    values: &ValueSet,
+) {
+    let in_one_house = |cells: &CellSet| {
+        sudoku
+            .all_constraints()
+            .iter()
+            .any(|house| cells.is_subset_of(house))
+    };
+    let cells_in_subset = CellSet::from_iter(subset.iter().copied());
+    // 所有单元格都在同一 House 中时为 naked subset
+    if in_one_house(&cells_in_subset) {
+        return;
+    }
+    let cells_of_values = values
+        .iter()
+        .map(|value| {
+            let cells = CellSet::from_iter(
+                subset
+                    .iter()
+                    .copied()
+                    .filter(|&cell| sudoku.candidates(cell).has(value)),
+            );
+            (value, cells)
+        })
+        .collect_vec();
+    if !cells_of_values.iter().all(|(_, cells)| in_one_house(cells)) {
+        return;
+    }
+
+    for (value, cells) in cells_of_values {
+        let mut eliminated = sudoku.possible_cells(value) - &cells_in_subset;
+        for cell in cells.iter() {
+            eliminated &= sudoku.house_union_of_cell(cell);
+        }
+        for cell in eliminated.iter() {
+            solution.add_elimination(
+                Technique::DistributedDisjointSubset,
+                format!(
+                    "{} only contain {}, and the cells containing each value see each other",
+                    sudoku.get_cellset_string(&cells_in_subset),
+                    values.iter().sorted().join(","),
+                ),
+                cell,
+                value,
+            );
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::CellIndex;
+use crate::utils::CellSet;
+
+use std::sync::LazyLock;
+
+// 模板：一个数字在整个盘面上的一种合法分布，每行、每列、每个 block 恰好一个单元格，共 46656 种。
+// 对每个数字，保留包含所有已填入该数字的单元格、且只使用该数字可能位置的模板：
+// 所有模板都包含的单元格必须填入该数字，没有任何模板包含的单元格不可能出现该数字。
+static TEMPLATES: LazyLock<Vec<u128>> = LazyLock::new(|| {
+    fn search(row: usize, used_columns: u16, template: u128, templates: &mut Vec<u128>) {
+        if row == 9 {
+            templates.push(template);
+            return;
+        }
+        for col in 0..9 {
+            if used_columns & (1 << col) != 0 {
+                continue;
+            }
+            // 同一 band 中之前的行不能使用同一 block
+            let block_used = (row / 3 * 3..row).any(|r| {
+                let cell_in_row = (template >> (r * 9)) as u16 & 0x1ff;
+                cell_in_row.trailing_zeros() as usize / 3 == col / 3
+            });
+            if block_used {
+                continue;
+            }
+            let cell = row * 9 + col;
+            search(
+                row + 1,
+                used_columns | (1 << col),
+                template | (1 << cell),
+                templates,
+            );
+        }
+    }
+
+    let mut templates = Vec::with_capacity(46656);
+    search(0, 0, 0, &mut templates);
+    templates
+});
+
+fn to_bitset(cells: impl Iterator<Item = CellIndex>) -> u128 {
+    cells.fold(0, |bitset, cell| bitset | (1 << cell))
+}
+
+pub fn solve_template(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for value in 1..=9 {
+        let placed = to_bitset(
+            (0..81)
+                .map(|cell| cell as CellIndex)
+                .filter(|&cell| sudoku.cell_value(cell) == Some(value)),
+        );
+        let allowed = placed | to_bitset(sudoku.possible_cells(value).iter());
+
+        let mut union = 0;
+        let mut intersection = u128::MAX;
+        for &template in TEMPLATES.iter() {
+            if template & !allowed == 0 && template & placed == placed {
+                union |= template;
+                intersection &= template;
+            }
+        }
+        if union == 0 {
+            // 没有合法的模板，盘面已经出现矛盾
+            continue;
+        }
+
+        for cell in CellSet::from_bitset(intersection & !placed).iter() {
+            solution.add_value_set(
+                Technique::Template,
+                format!(
+                    "for {}, {} is in all templates",
+                    value,
+                    sudoku.get_cell_name(cell)
+                ),
+                cell,
+                value,
+            );
+        }
+        for cell in CellSet::from_bitset(allowed & !union).iter() {
+            solution.add_elimination(
+                Technique::Template,
+                format!(
+                    "for {}, {} is in no template",
+                    value,
+                    sudoku.get_cell_name(cell)
+                ),
+                cell,
+                value,
+            );
+        }
+        return_in_fast_mode!(solution);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Techniques;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_template_count() {
+        assert_eq!(TEMPLATES.len(), 46656);
+    }
+
+    #[test]
+    fn test_template_placement() {
+        let sudoku = Sudoku::from_candidates(
+            "
+            26 1 25 7 3 9 8 46 2456
+            2689 245689 3 468 2468 2468 2459 1 7
+            7 24689 28 1468 5 12468 249 3 2469
+            1 25689 4 3 2689 2568 7 69 569
+            269 2569 7 1469 12469 12456 3459 8 34569
+            689 3 58 4689 7 4568 1 2 4569
+            3 28 6 5 1489 148 249 7 12489
+            5 28 1 4689 4689 7 2349 49 23489
+            4 7 9 2 18 3 6 5 18
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let singles = Techniques::from(["full_house", "naked_single", "hidden_single"].into_iter());
+        assert!(solver.solve_one_step(&singles).is_none());
+
+        let mut solution = SolutionRecorder::new();
+        solve_template(&solver, &mut solution);
+        assert_eq!(
+            solution.to_string(solver.sudoku()).lines().next(),
+            Some("[Template] for 2, r5c1 is in all templates => r5c1=2")
+        );
+    }
+}
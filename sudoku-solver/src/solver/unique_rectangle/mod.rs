@@ -0,0 +1,278 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder, SudokuSolver, Technique};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::{CellSet, ValueSet};
+
+use itertools::Itertools;
+
+// Unique Rectangle：位于两行、两列且恰好两个 block 中的四个单元格，若都只包含数字 a、b，
+// 则 a、b 可以在四个单元格中互换，数独将有多个解（deadly pattern）。假设数独有唯一解，则必须避免这种情况。
+// 只包含 a、b 的单元格称为 floor，其余的单元格称为 roof。
+// Type 1：只有一个 roof，则 roof 中不可能为 a 或 b。
+// Type 2：两个 roof 都恰好多出同一个数字 x，则 x 必在某个 roof 中，能同时看到两个 roof 的单元格中不可能为 x。
+// Type 3：两个 roof 位于同一个 House 中，将两个 roof 的多余数字视为一个单元格，与 House 中的其他单元格组成 naked subset。
+// Type 4：两个 roof 位于同一个 House 中，且 a 在该 House 中只能出现在两个 roof 中，则 roof 中不可能为 b。
+pub fn solve_unique_rectangle(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for cells in unfilled_rectangles(sudoku) {
+        let common_values =
+            ValueSet::intersection_multiple(cells.iter().map(|&cell| sudoku.candidates(cell)));
+        for (a, b) in common_values.iter().tuple_combinations() {
+            check_rectangle(sudoku, solution, &cells, a, b);
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+// 位于两行、两列且恰好两个 block 中的四个单元格，顺序为左上、右上、左下、右下
+fn rectangles(sudoku: &SudokuSolver) -> impl Iterator<Item = [CellIndex; 4]> + '_ {
+    (0..9)
+        .tuple_combinations()
+        .cartesian_product((0..9).tuple_combinations().collect_vec())
+        .filter(|&((row1, row2), (col1, col2))| (row1 / 3 == row2 / 3) != (col1 / 3 == col2 / 3))
+        .map(|((row1, row2), (col1, col2))| {
+            [
+                sudoku.cell_index(row1, col1),
+                sudoku.cell_index(row1, col2),
+                sudoku.cell_index(row2, col1),
+                sudoku.cell_index(row2, col2),
+            ]
+        })
+}
+
+fn unfilled_rectangles(sudoku: &SudokuSolver) -> impl Iterator<Item = [CellIndex; 4]> + '_ {
+    rectangles(sudoku).filter(|cells| cells.iter().all(|&cell| sudoku.cell_value(cell).is_none()))
+}
+
+fn check_rectangle(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    cells: &[CellIndex; 4],
+    a: CellValue,
+    b: CellValue,
+) {
+    let pair = ValueSet::from_iter([a, b]);
+    let roofs = cells
+        .iter()
+        .copied()
+        .filter(|&cell| sudoku.candidates(cell) != &pair)
+        .collect_vec();
+    let pattern = format!(
+        "{} would form a deadly pattern of {},{}",
+        sudoku.get_cellset_string(&cells.iter().copied().collect()),
+        a,
+        b
+    );
+
+    if let [roof] = roofs[..] {
+        let reason = format!(
+            "{}, type 1: only {} has other candidates",
+            pattern,
+            sudoku.get_cell_name(roof)
+        );
+        for value in [a, b] {
+            solution.add_elimination(Technique::UniqueRectangle, reason.clone(), roof, value);
+        }
+        return;
+    }
+
+    let [roof1, roof2] = roofs[..] else {
+        return;
+    };
+    let roof_set: CellSet = [roof1, roof2].into_iter().collect();
+    let roof_string = sudoku.get_cellset_string(&roof_set);
+    let extra1 = sudoku.candidates(roof1) - &pair;
+    let extra2 = sudoku.candidates(roof2) - &pair;
+
+    // Type 2
+    if extra1 == extra2 && extra1.size() == 1 {
+        let x = extra1.single_value();
+        let eliminated = &(sudoku.possible_cells(x) & sudoku.house_union_of_cell(roof1))
+            & sudoku.house_union_of_cell(roof2);
+        let reason = format!(
+            "{}, type 2: {} both have the extra candidate {}",
+            pattern, roof_string, x
+        );
+        for cell in eliminated.iter() {
+            solution.add_elimination(Technique::UniqueRectangle, reason.clone(), cell, x);
+        }
+        return_in_fast_mode!(solution);
+    }
+
+    let extra = &extra1 | &extra2;
+    for house in sudoku
+        .constraints_of_cell(roof1)
+        .iter()
+        .filter(|house| house.has(roof2))
+    {
+        // Type 3
+        let others = house
+            .iter()
+            .filter(|&cell| {
+                !roof_set.has(cell)
+                    && sudoku.cell_value(cell).is_none()
+                    && sudoku.candidates(cell).size() <= 4
+            })
+            .collect_vec();
+        for size in 1..=3 {
+            for subset in others.iter().copied().combinations(size) {
+                let mut values = extra.clone();
+                for &cell in subset.iter() {
+                    values |= sudoku.candidates(cell);
+                }
+                if values.size() != size + 1 {
+                    continue;
+                }
+                let reason = format!(
+                    "{}, type 3: the extra candidates of {} form a naked subset {} with {} in {}",
+                    pattern,
+                    roof_string,
+                    values.iter().join(","),
+                    sudoku.get_cellset_string(&subset.iter().copied().collect()),
+                    house.name()
+                );
+                for cell in house.iter() {
+                    if roof_set.has(cell) || subset.contains(&cell) {
+                        continue;
+                    }
+                    for value in values.iter() {
+                        if sudoku.can_fill(cell, value) {
+                            solution.add_elimination(
+                                Technique::UniqueRectangle,
+                                reason.clone(),
+                                cell,
+                                value,
+                            );
+                        }
+                    }
+                }
+                return_in_fast_mode!(solution);
+            }
+        }
+
+        // Type 4
+        for (locked, eliminated) in [(a, b), (b, a)] {
+            if **sudoku.get_possible_cells_for_house_and_value(house, locked) != roof_set {
+                continue;
+            }
+            let reason = format!(
+                "{}, type 4: {} in {} is locked to {}",
+                pattern,
+                locked,
+                house.name(),
+                roof_string
+            );
+            for roof in [roof1, roof2] {
+                solution.add_elimination(
+                    Technique::UniqueRectangle,
+                    reason.clone(),
+                    roof,
+                    eliminated,
+                );
+            }
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+// Hidden Rectangle：矩形的一个角 A 只包含 a、b，对角 D 所在的行与列中 a 都只能出现在矩形的两个角中。
+// 若 D 为 b，则 a 必在 D 所在行与列中的另外两个角，A 只能为 b，形成 deadly pattern，因此 D 中不可能为 b。
+pub fn solve_hidden_rectangle(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for cells in unfilled_rectangles(sudoku) {
+        for (corner, &floor) in cells.iter().enumerate() {
+            if sudoku.candidates(floor).size() != 2 {
+                continue;
+            }
+            // 与 A 同行、同列以及对角的单元格
+            let (same_row, same_col, diagonal) =
+                (cells[corner ^ 1], cells[corner ^ 2], cells[3 - corner]);
+            let pair = sudoku.candidates(floor).values();
+            if [same_row, same_col, diagonal]
+                .iter()
+                .any(|&cell| !pair.iter().all(|&value| sudoku.can_fill(cell, value)))
+            {
+                continue;
+            }
+            for (a, b) in [(pair[0], pair[1]), (pair[1], pair[0])] {
+                let Some(row) = sudoku.rows_with_only_two_possible_places(a).iter().find(
+                    |(_, (_, _, cell1), (_, _, cell2))| {
+                        is_same_pair((*cell1, *cell2), (same_col, diagonal))
+                    },
+                ) else {
+                    continue;
+                };
+                let Some(col) = sudoku.cols_with_only_two_possible_places(a).iter().find(
+                    |(_, (_, _, cell1), (_, _, cell2))| {
+                        is_same_pair((*cell1, *cell2), (same_row, diagonal))
+                    },
+                ) else {
+                    continue;
+                };
+                solution.add_elimination(
+                    Technique::HiddenRectangle,
+                    format!(
+                        "{} would form a deadly pattern of {},{}, {} only contains {},{} and {} only appears in the rectangle in {} and {}",
+                        sudoku.get_cellset_string(&cells.iter().copied().collect()),
+                        a,
+                        b,
+                        sudoku.get_cell_name(floor),
+                        a,
+                        b,
+                        a,
+                        row.0.name(),
+                        col.0.name(),
+                    ),
+                    diagonal,
+                    b,
+                );
+                return_in_fast_mode!(solution);
+            }
+        }
+    }
+}
+
+// Avoidable Rectangle：矩形的三个角已填入非题目给出的数字，A 为 a，与 A 同行、同列的角都为 b。
+// 若对角 D 为 a，则 a、b 可以在四个单元格中互换，数独将有多个解，因此 D 中不可能为 a。
+pub fn solve_avoidable_rectangle(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    for cells in rectangles(sudoku) {
+        let unfilled = cells
+            .iter()
+            .positions(|&cell| !sudoku.filled_cells().has(cell))
+            .collect_vec();
+        let [diagonal] = unfilled[..] else {
+            continue;
+        };
+        let corner = 3 - diagonal;
+        let (floor, same_row, same_col, diagonal) = (
+            cells[corner],
+            cells[corner ^ 1],
+            cells[corner ^ 2],
+            cells[diagonal],
+        );
+        let solved = [floor, same_row, same_col];
+        if solved.iter().any(|&cell| sudoku.givens().has(cell)) {
+            continue;
+        }
+        let (Some(a), Some(b)) = (sudoku.cell_value(floor), sudoku.cell_value(same_row)) else {
+            continue;
+        };
+        if sudoku.cell_value(same_col) != Some(b) || !sudoku.can_fill(diagonal, a) {
+            continue;
+        }
+        solution.add_elimination(
+            Technique::AvoidableRectangle,
+            format!(
+                "{} would form a deadly pattern of {},{} with the solved cells {}",
+                sudoku.get_cellset_string(&cells.iter().copied().collect()),
+                a,
+                b,
+                sudoku.get_cellset_string(&solved.into_iter().collect()),
+            ),
+            diagonal,
+            a,
+        );
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn is_same_pair(pair1: (CellIndex, CellIndex), pair2: (CellIndex, CellIndex)) -> bool {
+    pair1 == pair2 || pair1 == (pair2.1, pair2.0)
+}
@@ -1,6 +1,9 @@
 mod wwing;
+mod wxyzwing;
 mod xywing;
 
 pub use wwing::solve_w_wing;
+pub use wxyzwing::solve_wxyz_wing;
+pub(crate) use xywing::bivalue_cells;
 pub use xywing::solve_xy_wing;
 pub use xywing::solve_xyz_wing;
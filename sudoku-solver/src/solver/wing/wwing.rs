@@ -1,6 +1,6 @@
 use crate::solver::{return_in_fast_mode, SolutionRecorder};
 use crate::sudoku::{CellIndex, CellValue};
-use crate::utils::{combinations, CombinationOptions};
+use crate::utils::{combinations, CellSet, CombinationOptions};
 use crate::{SudokuSolver, Technique};
 
 use std::iter::FromIterator;
@@ -61,23 +61,14 @@ fn inner(
         debug_assert!(cell_a < cell_b);
         debug_assert!(cell_x < cell_y);
         if cell_x != cell_a && cell_y != cell_b && col_a == col_x && col_b == col_y {
-            for cell in eliminated.iter() {
-                solution.add_elimination(
-                    Technique::WWing,
-                    format!(
-                        "{} -{}- {} ={}= {} -{}- {} form a WWing",
-                        sudoku.get_cell_name(cell_a),
-                        value2,
-                        sudoku.get_cell_name(cell_x),
-                        value1,
-                        sudoku.get_cell_name(cell_y),
-                        value2,
-                        sudoku.get_cell_name(cell_b),
-                    ),
-                    cell,
-                    value2,
-                );
-            }
+            record_w_wing(
+                sudoku,
+                solution,
+                &eliminated,
+                [cell_a, cell_x, cell_y, cell_b],
+                value1,
+                value2,
+            );
             return_in_fast_mode!(solution);
         }
     }
@@ -86,24 +77,70 @@ fn inner(
         debug_assert!(cell_a < cell_b);
         debug_assert!(cell_x < cell_y);
         if cell_x != cell_a && cell_y != cell_b && row_a == row_x && row_b == row_y {
-            for cell in eliminated.iter() {
-                solution.add_elimination(
-                    Technique::WWing,
-                    format!(
-                        "{} -{}- {} ={}= {} -{}- {} form a WWing",
-                        sudoku.get_cell_name(cell_a),
-                        value2,
-                        sudoku.get_cell_name(cell_x),
-                        value1,
-                        sudoku.get_cell_name(cell_y),
-                        value2,
-                        sudoku.get_cell_name(cell_b),
-                    ),
-                    cell,
-                    value2,
-                );
-            }
+            record_w_wing(
+                sudoku,
+                solution,
+                &eliminated,
+                [cell_a, cell_x, cell_y, cell_b],
+                value1,
+                value2,
+            );
             return_in_fast_mode!(solution);
         }
     }
+
+    // 块内只有两个 value1 的位置时，同样构成 strong link
+    for block in sudoku.candidate_cells_in_blocks(value1) {
+        let [cell_x, cell_y] = block.values()[..] else {
+            continue;
+        };
+        if block.has(cell_a) || block.has(cell_b) {
+            continue;
+        }
+        let sees =
+            |cell1: CellIndex, cell2: CellIndex| sudoku.house_union_of_cell(cell1).has(cell2);
+        let (cell_x, cell_y) = if sees(cell_a, cell_x) && sees(cell_b, cell_y) {
+            (cell_x, cell_y)
+        } else if sees(cell_a, cell_y) && sees(cell_b, cell_x) {
+            (cell_y, cell_x)
+        } else {
+            continue;
+        };
+        record_w_wing(
+            sudoku,
+            solution,
+            &eliminated,
+            [cell_a, cell_x, cell_y, cell_b],
+            value1,
+            value2,
+        );
+        return_in_fast_mode!(solution);
+    }
+}
+
+fn record_w_wing(
+    sudoku: &SudokuSolver,
+    solution: &mut SolutionRecorder,
+    eliminated: &CellSet,
+    [cell_a, cell_x, cell_y, cell_b]: [CellIndex; 4],
+    value1: CellValue,
+    value2: CellValue,
+) {
+    for cell in eliminated.iter() {
+        solution.add_elimination(
+            Technique::WWing,
+            format!(
+                "{} -{}- {} ={}= {} -{}- {} form a WWing",
+                sudoku.get_cell_name(cell_a),
+                value2,
+                sudoku.get_cell_name(cell_x),
+                value1,
+                sudoku.get_cell_name(cell_y),
+                value2,
+                sudoku.get_cell_name(cell_b),
+            ),
+            cell,
+            value2,
+        );
+    }
 }
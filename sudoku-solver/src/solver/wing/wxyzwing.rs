@@ -0,0 +1,82 @@
+use crate::solver::{return_in_fast_mode, SolutionRecorder};
+use crate::sudoku::{CellIndex, CellValue};
+use crate::utils::{CellSet, ValueSet};
+use crate::{SudokuSolver, Technique};
+
+use std::iter::FromIterator;
+
+use itertools::Itertools;
+
+// WXYZ-Wing：四个单元格的候选数并集恰好为四个数字。
+// 若某个数字所在的单元格两两互相可见，则称该数字为 restricted，即该数字在四个单元格中至多出现一次。
+// 若只有一个数字 z 不是 restricted，则其余三个数字至多填入三个单元格，z 必在某个单元格中，
+// 能看到所有包含 z 的单元格的单元格中不可能出现 z。
+pub fn solve_wxyz_wing(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
+    let wing_cells = CellSet::from_iter(
+        sudoku
+            .unfilled_cells()
+            .iter()
+            .filter(|&c| (2..=4).contains(&sudoku.candidates(c).size())),
+    );
+
+    if wing_cells.size() < 4 {
+        return;
+    }
+
+    for pivot in wing_cells.iter() {
+        let possible_pincers = &wing_cells & sudoku.house_union_of_cell(pivot);
+        if possible_pincers.size() < 3 {
+            continue;
+        }
+
+        for pincers in possible_pincers.iter().combinations(3) {
+            let cells = [pivot, pincers[0], pincers[1], pincers[2]];
+            let wxyz = ValueSet::union_multiple(cells.iter().map(|&c| sudoku.candidates(c)));
+            if wxyz.size() != 4 {
+                continue;
+            }
+
+            let mut non_restricted = wxyz
+                .iter()
+                .filter(|&value| !is_restricted(sudoku, &cells, value));
+            let (Some(z), None) = (non_restricted.next(), non_restricted.next()) else {
+                continue;
+            };
+
+            let mut eliminated = sudoku.possible_cells(z).clone();
+            for &cell in cells.iter() {
+                if sudoku.can_fill(cell, z) {
+                    eliminated &= sudoku.house_union_of_cell(cell);
+                }
+                eliminated.remove(cell);
+            }
+            if eliminated.is_empty() {
+                continue;
+            }
+
+            for cell in eliminated.iter() {
+                solution.add_elimination(
+                    Technique::WXYZWing,
+                    format!(
+                        "the pivot {} and the pincers {} form a WXYZ-Wing with wxyz={}, {} is the only non-restricted candidate",
+                        sudoku.get_cell_name(pivot),
+                        pincers.iter().map(|&c| sudoku.get_cell_name(c)).join(","),
+                        wxyz.iter().join(""),
+                        z,
+                    ),
+                    cell,
+                    z,
+                );
+            }
+            return_in_fast_mode!(solution);
+        }
+    }
+}
+
+fn is_restricted(sudoku: &SudokuSolver, cells: &[CellIndex], value: CellValue) -> bool {
+    cells
+        .iter()
+        .filter(|&&cell| sudoku.can_fill(cell, value))
+        .tuple_combinations()
+        .all(|(&cell1, &cell2)| sudoku.house_union_of_cell(cell1).has(cell2))
+}
@@ -4,9 +4,12 @@ use crate::{SudokuSolver, Technique};
 
 use std::iter::FromIterator;
 
+pub(crate) fn bivalue_cells(sudoku: &SudokuSolver) -> CellSet {
+    CellSet::from_iter(sudoku.cells().filter(|&c| sudoku.candidates(c).size() == 2))
+}
+
 pub fn solve_xy_wing(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
-    let bivalue_cells =
-        CellSet::from_iter(sudoku.cells().filter(|&c| sudoku.candidates(c).size() == 2));
+    let bivalue_cells = bivalue_cells(sudoku);
 
     if bivalue_cells.size() < 2 {
         return;
@@ -66,8 +69,7 @@ pub fn solve_xy_wing(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
 }
 
 pub fn solve_xyz_wing(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
-    let bivalue_cells =
-        CellSet::from_iter(sudoku.cells().filter(|&c| sudoku.candidates(c).size() == 2));
+    let bivalue_cells = bivalue_cells(sudoku);
 
     if bivalue_cells.size() < 2 {
         return;
@@ -109,7 +111,7 @@ pub fn solve_xyz_wing(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
                     solution.add_elimination(
                         Technique::XYZWing,
                         format!(
-                            "the pivot {} and the pincers {} and {} form an XY-Wing with xyz={}{}{}",
+                            "the pivot {} and the pincers {} and {} form an XYZ-Wing with xyz={}{}{}",
                             sudoku.get_cell_name(cell_xy),
                             sudoku.get_cell_name(cell_xz),
                             sudoku.get_cell_name(cell_yz),
@@ -126,3 +128,35 @@ pub fn solve_xyz_wing(sudoku: &SudokuSolver, solution: &mut SolutionRecorder) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Techniques;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_xyz_wing() {
+        let techniques = Techniques::from(["xyz_wing"].into_iter());
+        // r2c5 sees both pincers r1c5 and r2c2, but not the pivot r1c1
+        let sudoku = Sudoku::from_candidates(
+            "
+            123 456789 34 456789 13 456789 456789 456789 456789
+            456789 23 456789 456789 34 456789 456789 456789 456789
+            456789 456789 456789 456789 456789 456789 456789 456789 456789
+            456789 456789 456789 456789 456789 456789 456789 456789 456789
+            456789 456789 456789 456789 456789 456789 456789 456789 456789
+            456789 456789 456789 456789 456789 456789 456789 456789 456789
+            456789 456789 456789 456789 456789 456789 456789 456789 456789
+            456789 456789 456789 456789 456789 456789 456789 456789 456789
+            456789 456789 456789 456789 456789 456789 456789 456789 456789
+            ",
+        );
+        let solver = SudokuSolver::new(sudoku);
+        let solution = solver.solve_one_step(&techniques).unwrap();
+        assert_eq!(
+            solution.to_string(solver.sudoku()).trim(),
+            "[XYZWing] the pivot r1c1 and the pincers r1c5 and r2c2 form an XYZ-Wing with xyz=123 => r1c3<>3"
+        );
+    }
+}
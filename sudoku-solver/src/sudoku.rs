@@ -1,6 +1,9 @@
-use crate::utils::{CellSet, ValueSet};
+use crate::solver::guess::State;
+use crate::solver::PEER_BITSETS;
+use crate::utils::{CellSet, Rng, ValueSet};
 
 use itertools::Itertools;
+use std::fmt::Display;
 use wasm_bindgen::prelude::*;
 
 pub type CellIndex = u8;
@@ -14,8 +17,75 @@ pub struct Sudoku {
     candidates: Vec<ValueSet>,
     // value -> possible cell positions for that value
     possible_positions: Vec<CellSet>,
+    // the givens of the puzzle, which cannot be changed by `set_cell` unless forced
+    locked: CellSet,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SudokuError {
+    /// The position (row, column) is outside of the 9x9 grid.
+    InvalidPosition(usize, usize),
+    /// The value is not a digit between 1 and 9.
+    InvalidValue(CellValue),
+    /// Two cells sharing a house are filled with the same value.
+    Conflict(CellIndex, CellIndex),
+    /// The string is not a cell name like `r1c1`.
+    InvalidCellName(String),
+    /// The string is not a state produced by `Sudoku::to_state_string`.
+    InvalidState(String),
+    /// The cell is a given of the puzzle and cannot be changed.
+    GivenCell(CellIndex),
+    /// The string contains fewer than 81 cells.
+    TooFewCells(usize),
+    /// The string contains more than 81 cells.
+    TooManyCells(usize),
+    /// The string contains a letter or a digit that is neither a value nor an empty cell.
+    InvalidDigit(char),
+}
+
+impl std::str::FromStr for Sudoku {
+    type Err = SudokuError;
+
+    /// Same as `Sudoku::try_from_values`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_values(s)
+    }
+}
+
+impl Display for SudokuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SudokuError::InvalidPosition(row, col) => {
+                write!(f, "position ({}, {}) is out of the grid", row, col)
+            }
+            SudokuError::InvalidValue(value) => write!(f, "{} is not a valid value", value),
+            SudokuError::Conflict(cell1, cell2) => write!(
+                f,
+                "r{}c{} and r{}c{} have the same value",
+                cell1 / 9 + 1,
+                cell1 % 9 + 1,
+                cell2 / 9 + 1,
+                cell2 % 9 + 1
+            ),
+            SudokuError::InvalidCellName(name) => write!(f, "{:?} is not a valid cell name", name),
+            SudokuError::InvalidState(reason) => write!(f, "invalid state: {}", reason),
+            SudokuError::GivenCell(idx) => {
+                write!(
+                    f,
+                    "r{}c{} is a given and cannot be changed",
+                    idx / 9 + 1,
+                    idx % 9 + 1
+                )
+            }
+            SudokuError::TooFewCells(len) => write!(f, "expected 81 cells, found only {}", len),
+            SudokuError::TooManyCells(len) => write!(f, "expected 81 cells, found {}", len),
+            SudokuError::InvalidDigit(ch) => write!(f, "{:?} is not a valid digit", ch),
+        }
+    }
+}
+
+impl std::error::Error for SudokuError {}
+
 #[wasm_bindgen]
 impl Sudoku {
     pub(crate) fn get_candidates(&self, idx: CellIndex) -> &ValueSet {
@@ -52,6 +122,15 @@ impl Sudoku {
         self.board[idx as usize]
     }
 
+    /// Whether the cell is one of the givens of the puzzle.
+    pub fn is_given(&self, idx: CellIndex) -> bool {
+        self.locked.has(idx)
+    }
+
+    pub(crate) fn givens(&self) -> &CellSet {
+        &self.locked
+    }
+
     pub(crate) fn get_cell_position(&self, row: usize, col: usize) -> CellIndex {
         (row * 9 + col) as u8
     }
@@ -60,25 +139,45 @@ impl Sudoku {
         format!("r{}c{}", idx / 9 + 1, idx % 9 + 1)
     }
 
-    pub fn from_values(str: &str) -> Self {
-        let mut board = Vec::with_capacity(81);
-        for ch in str.chars() {
-            if ch.is_digit(10) {
-                let digit = ch.to_digit(10).unwrap() as u8;
-                board.push(Some(digit));
-            } else if ch == '.' || ch == '_' {
-                board.push(None);
-            }
+    /// Parses a cell name like `r1c1`. It is the inverse of `get_cell_name`.
+    pub(crate) fn parse_cell_name(&self, name: &str) -> Result<CellIndex, SudokuError> {
+        let invalid = || SudokuError::InvalidCellName(name.to_string());
+        let bytes = name.as_bytes();
+        if bytes.len() != 4 || bytes[0] != b'r' || bytes[2] != b'c' {
+            return Err(invalid());
         }
-        let candidates = vec![ValueSet::new(); 81];
-        let possible_positions = vec![CellSet::new(); 10];
+        let row = (bytes[1] as char).to_digit(10).ok_or_else(invalid)?;
+        let col = (bytes[3] as char).to_digit(10).ok_or_else(invalid)?;
+        if !(1..=9).contains(&row) || !(1..=9).contains(&col) {
+            return Err(invalid());
+        }
+        Ok(self.get_cell_position(row as usize - 1, col as usize - 1))
+    }
+
+    pub fn empty() -> Self {
         Self {
-            board,
-            candidates,
-            possible_positions,
+            board: vec![None; 81],
+            candidates: vec![ValueSet::new(); 81],
+            possible_positions: vec![CellSet::new(); 10],
+            locked: CellSet::new(),
         }
     }
 
+    /// Panics if the string does not contain exactly 81 cells, see `Sudoku::try_from_values`.
+    pub fn from_values(str: &str) -> Self {
+        Self::try_from_values(str).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `Sudoku::try_from_values`, exported so that JavaScript gets an exception instead of
+    /// the trap of `from_values` for an invalid string.
+    pub fn parse(str: &str) -> Result<Sudoku, JsError> {
+        Ok(Self::try_from_values(str)?)
+    }
+
+    /// Parses a grid of candidates, where the candidates of a cell are written as consecutive digits
+    /// and cells are separated by other characters. An empty cell (see `Sudoku::try_from_values`)
+    /// has all the candidates. The grid does not tell givens from solved cells, so no cell is a
+    /// given.
     pub fn from_candidates(str: &str) -> Self {
         let mut board = vec![None; 81];
         let mut candidates = vec![ValueSet::new(); 81];
@@ -87,18 +186,18 @@ impl Sudoku {
         let mut idx = 0;
         let mut waiting_next_digit = false;
         while let Some(ch) = chars.next() {
-            if ch.is_digit(10) {
-                waiting_next_digit = true;
-                let digit = ch.to_digit(10).unwrap() as CellValue;
-                candidates[idx].add(digit);
-                possible_positions[digit as usize].add(idx as CellIndex);
-            } else if ch == '.' {
+            if is_empty_cell(ch) {
                 debug_assert!(!waiting_next_digit);
                 for digit in 1..=9 {
                     candidates[idx].add(digit);
                     possible_positions[digit as usize].add(idx as CellIndex);
                 }
                 idx += 1;
+            } else if ch.is_digit(10) {
+                waiting_next_digit = true;
+                let digit = ch.to_digit(10).unwrap() as CellValue;
+                candidates[idx].add(digit);
+                possible_positions[digit as usize].add(idx as CellIndex);
             } else {
                 if waiting_next_digit {
                     assert!(candidates[idx].size() > 0);
@@ -127,6 +226,7 @@ impl Sudoku {
             board,
             candidates,
             possible_positions,
+            locked: CellSet::new(),
         }
     }
 
@@ -206,4 +306,697 @@ impl Sudoku {
         }
         s
     }
+
+    /// Renders every cell as a 3x3 mini-grid with candidate `d` at position `d` (1 top-left,
+    /// 9 bottom-right) and blanks for absent candidates. Filled cells show `<v>` in the middle.
+    pub fn to_pencilmark_grid(&self) -> String {
+        let mut s = String::new();
+        let horizontal_line = "+-------------+-------------+-------------+\n";
+        let empty_line = "|             |             |             |\n";
+        s.push_str(horizontal_line);
+        for row in 0..9 {
+            let minigrids = (0..9)
+                .map(|col| self.pencilmark_minigrid(self.get_cell_position(row, col)))
+                .collect_vec();
+            for line in 0..3 {
+                s.push('|');
+                for (col, minigrid) in minigrids.iter().enumerate() {
+                    s.push(' ');
+                    s.push_str(&minigrid[line]);
+                    if col % 3 == 2 {
+                        s.push_str(" |");
+                    }
+                }
+                s.push('\n');
+            }
+            if row % 3 == 2 {
+                s.push_str(horizontal_line);
+            } else {
+                s.push_str(empty_line);
+            }
+        }
+        s
+    }
+}
+
+/// Renders the values as a 9x9 grid with `.` for unfilled cells, e.g. for `println!("{}", sudoku)`.
+/// See `Sudoku::to_value_string` for the compact form.
+impl Display for Sudoku {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let horizontal_line = "+-------+-------+-------+";
+        for row in 0..9 {
+            if row % 3 == 0 {
+                writeln!(f, "{}", horizontal_line)?;
+            }
+            for col in 0..9 {
+                if col % 3 == 0 {
+                    write!(f, "| ")?;
+                }
+                match self.get_cell_value(self.get_cell_position(row, col)) {
+                    Some(value) => write!(f, "{} ", value)?,
+                    None => write!(f, ". ")?,
+                }
+            }
+            writeln!(f, "|")?;
+        }
+        writeln!(f, "{}", horizontal_line)
+    }
+}
+
+impl Sudoku {
+    /// Counts the solutions of the filled values, stopping at `limit`. Pass `limit = 2` to check
+    /// uniqueness cheaply.
+    pub fn solution_count(&self, limit: usize) -> usize {
+        State::from_values(&self.to_value_string()).count_solutions(limit)
+    }
+
+    /// Solves the filled values with the guess solver and returns the 81 digits of a solution,
+    /// or `None` if the sudoku has no solution.
+    pub fn solve_fast(&self) -> Option<String> {
+        let mut state = State::from_values(&self.to_value_string());
+        state.solve_with_blame().ok()?;
+        Some(state.to_value_string())
+    }
+
+    /// Whether the sudoku has exactly one solution.
+    pub fn has_unique_solution(&self) -> bool {
+        self.solution_count(2) == 1
+    }
+
+    /// Whether the sudoku has a unique solution which is lost when any of its values is removed.
+    pub fn is_minimal(&self) -> bool {
+        if !self.has_unique_solution() {
+            return false;
+        }
+        let values = self.to_value_string();
+        values
+            .char_indices()
+            .filter(|&(_, ch)| ch != '.')
+            .all(|(idx, _)| !Self::from_values(&remove_value(&values, idx)).has_unique_solution())
+    }
+
+    /// Removes values in an order shuffled by `seed` as long as the solution stays unique. The
+    /// result is a minimal puzzle with the same solution, see `is_minimal`. `self` is expected to
+    /// have a unique solution.
+    pub fn minimize(&self, seed: u64) -> Sudoku {
+        let mut values = self.to_value_string();
+        let mut cells = values
+            .char_indices()
+            .filter(|&(_, ch)| ch != '.')
+            .map(|(idx, _)| idx)
+            .collect_vec();
+        Rng::new(seed).shuffle(&mut cells);
+        for idx in cells {
+            let removed = remove_value(&values, idx);
+            if Self::from_values(&removed).has_unique_solution() {
+                values = removed;
+            }
+        }
+        Self::from_values(&values)
+    }
+
+    fn pencilmark_minigrid(&self, idx: CellIndex) -> [String; 3] {
+        if let Some(value) = self.get_cell_value(idx) {
+            return ["   ".to_string(), format!("<{}>", value), "   ".to_string()];
+        }
+        let candidates = self.get_candidates(idx);
+        [0, 1, 2].map(|line| {
+            (1..=3)
+                .map(|pos| {
+                    let value = line * 3 + pos;
+                    if candidates.has(value) {
+                        char::from(b'0' + value)
+                    } else {
+                        ' '
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Parses a string of 81 cells, where a digit from 1 to 9 is a given and `.`, `_`, `0` or a
+    /// space is an empty cell. Other letters and digits are invalid, and the remaining characters
+    /// (e.g. line breaks and grid lines) are ignored.
+    pub fn try_from_values(str: &str) -> Result<Self, SudokuError> {
+        let mut board = Vec::with_capacity(81);
+        for ch in str.chars() {
+            match ch {
+                '1'..='9' => board.push(Some(ch as u8 - b'0')),
+                _ if is_empty_value(ch) => board.push(None),
+                _ if ch.is_alphanumeric() => return Err(SudokuError::InvalidDigit(ch)),
+                _ => {}
+            }
+        }
+        if board.len() < 81 {
+            return Err(SudokuError::TooFewCells(board.len()));
+        }
+        if board.len() > 81 {
+            return Err(SudokuError::TooManyCells(board.len()));
+        }
+        let candidates = vec![ValueSet::new(); 81];
+        let possible_positions = vec![CellSet::new(); 10];
+        let locked = CellSet::from_iter(
+            (0..board.len())
+                .filter(|&idx| board[idx].is_some())
+                .map(|idx| idx as CellIndex),
+        );
+        Ok(Self {
+            board,
+            candidates,
+            possible_positions,
+            locked,
+        })
+    }
+
+    /// Fills the cell with `value`, or clears it if `value` is `None`. Changing a given fails with
+    /// `SudokuError::GivenCell` unless `force` is true.
+    ///
+    /// A cleared cell gets back the candidates that no peer holds, and its old value is restored as
+    /// a candidate of the pencil-marked peers where no other peer holds it.
+    pub fn set_cell(
+        &mut self,
+        idx: CellIndex,
+        value: Option<CellValue>,
+        force: bool,
+    ) -> Result<(), SudokuError> {
+        if self.is_given(idx) && !force {
+            return Err(SudokuError::GivenCell(idx));
+        }
+        match value {
+            Some(value) if !(1..=9).contains(&value) => Err(SudokuError::InvalidValue(value)),
+            Some(value) => {
+                self.fill(idx, value);
+                Ok(())
+            }
+            None => {
+                let old_value = self.board[idx as usize].take();
+                for value in 1..=9 {
+                    if !self.is_blocked(idx, value) {
+                        self.add_candidate(idx, value);
+                    }
+                }
+                if let Some(value) = old_value {
+                    for peer in CellSet::from_bitset_const(PEER_BITSETS[idx as usize]).iter() {
+                        if self.board[peer as usize].is_none()
+                            && !self.candidates[peer as usize].is_empty()
+                            && !self.is_blocked(peer, value)
+                        {
+                            self.add_candidate(peer, value);
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether a peer of the cell is filled with `value`.
+    fn is_blocked(&self, idx: CellIndex, value: CellValue) -> bool {
+        CellSet::from_bitset_const(PEER_BITSETS[idx as usize])
+            .iter()
+            .any(|peer| self.board[peer as usize] == Some(value))
+    }
+
+    /// Cells filled in both sudokus with different values.
+    pub fn diff(&self, other: &Sudoku) -> Vec<CellIndex> {
+        (0..81)
+            .filter(|&idx| match (self.board[idx], other.board[idx]) {
+                (Some(value), Some(other_value)) => value != other_value,
+                _ => false,
+            })
+            .map(|idx| idx as CellIndex)
+            .collect()
+    }
+
+    /// Serializes the values, the givens and the candidates, so that the state can be restored
+    /// exactly by `from_state_string`. Cells are separated by `,`: a filled cell is written as its
+    /// value, prefixed with `!` if it is a given, and an unfilled cell is written as `.` followed by
+    /// its candidates, e.g. `!5,.124,3,...`.
+    pub fn to_state_string(&self) -> String {
+        (0..81)
+            .map(|idx| match self.board[idx] {
+                Some(value) if self.is_given(idx as CellIndex) => format!("!{}", value),
+                Some(value) => value.to_string(),
+                None => format!(".{}", self.candidates[idx].iter().join("")),
+            })
+            .join(",")
+    }
+
+    pub fn from_state_string(str: &str) -> Result<Self, SudokuError> {
+        let cells = str.trim().split(',').collect_vec();
+        if cells.len() != 81 {
+            return Err(SudokuError::InvalidState(format!(
+                "expected 81 cells, found {}",
+                cells.len()
+            )));
+        }
+        let mut sudoku = Sudoku::empty();
+        for (idx, cell) in cells.into_iter().enumerate() {
+            let invalid = || SudokuError::InvalidState(format!("invalid cell {:?}", cell));
+            let (is_given, value) = match cell.strip_prefix('!') {
+                Some(value) => (true, value),
+                None => (false, cell),
+            };
+            let digits = value
+                .trim_start_matches('.')
+                .chars()
+                .map(|ch| match ch.to_digit(10) {
+                    Some(digit @ 1..=9) => Ok(digit as CellValue),
+                    _ => Err(invalid()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if value.starts_with('.') && !is_given {
+                for value in digits {
+                    sudoku.add_candidate(idx as CellIndex, value);
+                }
+            } else if !value.starts_with('.') && digits.len() == 1 {
+                sudoku.board[idx] = Some(digits[0]);
+                if is_given {
+                    sudoku.locked.add(idx as CellIndex);
+                }
+            } else {
+                return Err(invalid());
+            }
+        }
+        Ok(sudoku)
+    }
+}
+
+/// Builds a `Sudoku` cell by cell, e.g.
+/// `SudokuBuilder::new().set(0, 0, 5).set(0, 1, 3).build()`.
+#[derive(Debug, Clone)]
+pub struct SudokuBuilder {
+    board: Vec<Option<CellValue>>,
+    error: Option<SudokuError>,
+}
+
+impl SudokuBuilder {
+    pub fn new() -> Self {
+        Self {
+            board: vec![None; 81],
+            error: None,
+        }
+    }
+
+    /// Fills the cell at (row, col) with `value`. Both row and col are 0-based.
+    /// Invalid input is reported by `build`.
+    pub fn set(mut self, row: usize, col: usize, value: CellValue) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        if row >= 9 || col >= 9 {
+            self.error = Some(SudokuError::InvalidPosition(row, col));
+        } else if !(1..=9).contains(&value) {
+            self.error = Some(SudokuError::InvalidValue(value));
+        } else {
+            self.board[row * 9 + col] = Some(value);
+        }
+        self
+    }
+
+    pub fn build(self) -> Result<Sudoku, SudokuError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let house_of_cell = |idx: usize| (idx / 9, 9 + idx % 9, 18 + idx / 27 * 3 + idx % 9 / 3);
+        for cell1 in 0..81 {
+            let Some(value) = self.board[cell1] else {
+                continue;
+            };
+            let (row1, col1, block1) = house_of_cell(cell1);
+            for cell2 in 0..cell1 {
+                let (row2, col2, block2) = house_of_cell(cell2);
+                if self.board[cell2] == Some(value)
+                    && (row1 == row2 || col1 == col2 || block1 == block2)
+                {
+                    return Err(SudokuError::Conflict(
+                        cell2 as CellIndex,
+                        cell1 as CellIndex,
+                    ));
+                }
+            }
+        }
+        let locked = CellSet::from_iter((0..81).filter(|&idx| self.board[idx as usize].is_some()));
+        Ok(Sudoku {
+            board: self.board,
+            locked,
+            ..Sudoku::empty()
+        })
+    }
+}
+
+impl Default for SudokuBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `ch` stands for an empty cell in a string of values or candidates. Spaces are not empty
+/// cells here since they separate the cells of a candidate grid.
+pub(crate) fn is_empty_cell(ch: char) -> bool {
+    matches!(ch, '.' | '_' | '0')
+}
+
+/// Whether `ch` stands for an empty cell in a string of values, where a space is also a blank.
+pub(crate) fn is_empty_value(ch: char) -> bool {
+    ch == ' ' || is_empty_cell(ch)
+}
+
+fn remove_value(values: &str, idx: usize) -> String {
+    let mut values = values.to_string();
+    values.replace_range(idx..idx + 1, ".");
+    values
+}
+
+/// Checks that `grid` is a completely filled grid of 81 digits where every row, column and block
+/// contains each of 1 to 9 exactly once.
+pub fn is_valid_solution(grid: &str) -> bool {
+    let digits = grid.as_bytes();
+    if digits.len() != 81 || !digits.iter().all(|digit| (b'1'..=b'9').contains(digit)) {
+        return false;
+    }
+    let mut seen = [0u16; 27];
+    for (idx, &digit) in digits.iter().enumerate() {
+        let (row, col) = (idx / 9, idx % 9);
+        let bit = 1 << (digit - b'0');
+        for house in [row, 9 + col, 18 + row / 3 * 3 + col / 3] {
+            if seen[house] & bit != 0 {
+                return false;
+            }
+            seen[house] |= bit;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{SudokuSolver, Techniques};
+
+    #[test]
+    fn test_builder() {
+        let solution =
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+        let mut builder = SudokuBuilder::new();
+        for (idx, ch) in solution.chars().enumerate() {
+            // Leave the main diagonal empty for the solver
+            if idx / 9 != idx % 9 {
+                builder = builder.set(idx / 9, idx % 9, ch.to_digit(10).unwrap() as CellValue);
+            }
+        }
+        let sudoku = builder.build().unwrap();
+        assert_eq!(
+            sudoku.to_value_string(),
+            ".346789126.219534819.342567859.614234268.379171392.856961537.842874196.534528617."
+        );
+
+        let mut solver = SudokuSolver::new(sudoku);
+        solver.initialize_candidates();
+        let techniques = Techniques::new();
+        while let Some(step) = solver.solve_one_step(&techniques) {
+            solver.apply_step(&step);
+        }
+        assert!(solver.is_completed());
+        assert_eq!(solver.sudoku().to_value_string(), solution);
+    }
+
+    #[test]
+    fn test_state_string() {
+        let sudoku = Sudoku::from_candidates(
+            "5 3 124 26 7 2468 1489 1249 248 6 247 247 1 9 5 3478 234 2478",
+        );
+        let state = sudoku.to_state_string();
+        assert!(state.starts_with("5,3,.124,.26,7,.2468,"));
+        let restored = Sudoku::from_state_string(&state).unwrap();
+        assert_eq!(restored.to_state_string(), state);
+        assert_eq!(restored.to_candidate_string(), sudoku.to_candidate_string());
+        assert!(Sudoku::from_state_string("5,3").is_err());
+        assert!(Sudoku::from_state_string(&state.replace(".124", "124")).is_err());
+
+        let sudoku = Sudoku::from_values(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        );
+        let state = sudoku.to_state_string();
+        assert!(state.starts_with("!5,!3,.,.,!7,"));
+        let restored = Sudoku::from_state_string(&state).unwrap();
+        assert!(restored.is_given(0));
+        assert!(!restored.is_given(2));
+        assert_eq!(restored.to_state_string(), state);
+        assert!(Sudoku::from_state_string(&state.replacen("!5", "!.5", 1)).is_err());
+    }
+
+    #[test]
+    fn test_set_cell() {
+        let mut sudoku = Sudoku::from_values(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        );
+        assert!(sudoku.is_given(0));
+        assert!(!sudoku.is_given(2));
+
+        assert_eq!(
+            sudoku.set_cell(0, Some(1), false),
+            Err(SudokuError::GivenCell(0))
+        );
+        assert_eq!(
+            sudoku.set_cell(0, None, false),
+            Err(SudokuError::GivenCell(0))
+        );
+        assert_eq!(sudoku.get_cell_value(0), Some(5));
+
+        assert_eq!(sudoku.set_cell(2, Some(4), false), Ok(()));
+        assert_eq!(sudoku.get_cell_value(2), Some(4));
+        assert_eq!(sudoku.set_cell(2, Some(1), false), Ok(()));
+        assert_eq!(sudoku.set_cell(2, None, false), Ok(()));
+        assert_eq!(sudoku.get_cell_value(2), None);
+        assert!(!sudoku.is_given(2));
+
+        assert_eq!(sudoku.set_cell(0, Some(1), true), Ok(()));
+        assert_eq!(sudoku.get_cell_value(0), Some(1));
+    }
+
+    #[test]
+    fn test_clear_cell_restores_candidates() {
+        let mut solver = SudokuSolver::new(Sudoku::from_values(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        ));
+        solver.initialize_candidates();
+        let mut sudoku = solver.take_sudoku();
+        let original = sudoku.to_candidate_string();
+
+        // fill r1c3 with 4 and eliminate 4 from its peers
+        assert_eq!(sudoku.set_cell(2, Some(4), false), Ok(()));
+        for peer in CellSet::from_bitset_const(PEER_BITSETS[2]).iter() {
+            sudoku.remove_candidate(peer, 4);
+        }
+        assert_eq!(sudoku.set_cell(2, None, false), Ok(()));
+        assert_eq!(sudoku.get_candidates(2).iter().collect_vec(), vec![1, 2, 4]);
+        assert_eq!(sudoku.to_candidate_string(), original);
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        assert_eq!(Sudoku::empty().to_value_string(), ".".repeat(81));
+        assert_eq!(
+            SudokuBuilder::new()
+                .set(0, 0, 1)
+                .set(2, 2, 1)
+                .build()
+                .unwrap_err(),
+            SudokuError::Conflict(0, 20)
+        );
+        assert_eq!(
+            SudokuBuilder::new().set(0, 9, 1).build().unwrap_err(),
+            SudokuError::InvalidPosition(0, 9)
+        );
+        assert_eq!(
+            SudokuBuilder::new().set(0, 0, 0).build().unwrap_err(),
+            SudokuError::InvalidValue(0)
+        );
+    }
+
+    #[test]
+    fn test_empty_cell_characters() {
+        let values =
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        let candidates = values.chars().join(" ");
+        let state_values = State::from_values(values).to_value_string();
+        for blank in ["_", "0"] {
+            assert_eq!(
+                Sudoku::from_values(&values.replace('.', blank)).to_value_string(),
+                values
+            );
+            assert_eq!(
+                Sudoku::from_candidates(&candidates.replace('.', blank)).to_value_string(),
+                values
+            );
+            assert_eq!(
+                State::from_values(&values.replace('.', blank)).to_value_string(),
+                state_values
+            );
+        }
+        // A space is a blank in value strings, but separates the cells of a candidate grid
+        assert_eq!(
+            Sudoku::from_values(&values.replace('.', " ")).to_value_string(),
+            values
+        );
+        assert_eq!(
+            State::from_values(&values.replace('.', " ")).to_value_string(),
+            state_values
+        );
+    }
+
+    #[test]
+    fn test_from_values_length() {
+        let values =
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        assert_eq!(
+            Sudoku::try_from_values(&values[..80]).unwrap_err(),
+            SudokuError::TooFewCells(80)
+        );
+        assert_eq!(
+            Sudoku::try_from_values(values).unwrap().to_value_string(),
+            values
+        );
+        assert_eq!(
+            Sudoku::try_from_values(&format!("{}.", values)).unwrap_err(),
+            SudokuError::TooManyCells(82)
+        );
+        assert_eq!(
+            format!("x{}", &values[1..]).parse::<Sudoku>().unwrap_err(),
+            SudokuError::InvalidDigit('x')
+        );
+        assert_eq!(values.parse::<Sudoku>().unwrap().to_value_string(), values);
+        assert_eq!(
+            values
+                .replace('.', "0")
+                .parse::<Sudoku>()
+                .unwrap()
+                .to_value_string(),
+            values
+        );
+    }
+
+    #[test]
+    fn test_pencilmark_grid() {
+        let sudoku = Sudoku::from_candidates(&format!("5 124 {}", ".".repeat(79)));
+        assert_eq!(
+            sudoku.pencilmark_minigrid(1),
+            ["12 ".to_string(), "4  ".to_string(), "   ".to_string()]
+        );
+        assert_eq!(
+            sudoku.pencilmark_minigrid(0),
+            ["   ".to_string(), "<5>".to_string(), "   ".to_string()]
+        );
+
+        let grid = sudoku.to_pencilmark_grid();
+        let lines = grid.lines().collect_vec();
+        assert_eq!(lines.len(), 37);
+        assert!(lines.iter().all(|line| line.chars().count() == 43));
+        assert_eq!(lines[0], "+-------------+-------------+-------------+");
+        assert_eq!(lines[1], "|     12  123 | 123 123 123 | 123 123 123 |");
+        assert_eq!(lines[2], "| <5> 4   456 | 456 456 456 | 456 456 456 |");
+        assert_eq!(lines[3], "|         789 | 789 789 789 | 789 789 789 |");
+    }
+
+    #[test]
+    fn test_is_valid_solution() {
+        let solution =
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+        assert!(is_valid_solution(solution));
+
+        // r1c1 and r1c2 swapped keep the rows valid but break the columns
+        let swapped = format!("35{}", &solution[2..]);
+        assert!(!is_valid_solution(&swapped));
+        // a repeated digit in r1
+        let repeated = format!("55{}", &solution[2..]);
+        assert!(!is_valid_solution(&repeated));
+
+        assert!(!is_valid_solution(&solution[..80]));
+        assert!(!is_valid_solution(&format!("{}1", solution)));
+        assert!(!is_valid_solution(&format!(".{}", &solution[1..])));
+        assert!(!is_valid_solution(&format!("0{}", &solution[1..])));
+    }
+
+    #[test]
+    fn test_display() {
+        let sudoku = Sudoku::from_values(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        );
+        assert_eq!(
+            sudoku.to_string(),
+            "\
++-------+-------+-------+
+| 5 3 . | . 7 . | . . . |
+| 6 . . | 1 9 5 | . . . |
+| . 9 8 | . . . | . 6 . |
++-------+-------+-------+
+| 8 . . | . 6 . | . . 3 |
+| 4 . . | 8 . 3 | . . 1 |
+| 7 . . | . 2 . | . . 6 |
++-------+-------+-------+
+| . 6 . | . . . | 2 8 . |
+| . . . | 4 1 9 | . . 5 |
+| . . . | . 8 . | . 7 9 |
++-------+-------+-------+
+"
+        );
+    }
+
+    #[test]
+    fn test_solve_fast() {
+        let sudoku = Sudoku::from_values(
+            "6.....3...5..9..8...2..6..98.....7...7..5..4......1..51..3..5...4..2..6...8..7..2",
+        );
+        assert_eq!(
+            sudoku.solve_fast().unwrap(),
+            "689514327457293681312876459835942716971658243264731895126389574743125968598467132"
+        );
+
+        let contradictory = Sudoku::from_values(&format!("11{}", ".".repeat(79)));
+        assert_eq!(contradictory.solve_fast(), None);
+    }
+
+    #[test]
+    fn test_solution_count() {
+        let puzzle = Sudoku::from_values(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        );
+        assert_eq!(puzzle.solution_count(2), 1);
+
+        // the 6s and 7s in r1c4,r1c5,r4c4,r4c5 of the solution can be swapped once removed
+        let ambiguous = Sudoku::from_values(
+            "534..8912672195348198342567859..1423426853791713924856961537284287419635345286179",
+        );
+        assert_eq!(ambiguous.solution_count(2), 2);
+        assert_eq!(ambiguous.solution_count(1), 1);
+        assert_eq!(ambiguous.solution_count(0), 0);
+    }
+
+    #[test]
+    fn test_minimize() {
+        let solution =
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+        let sudoku = Sudoku::from_values(solution);
+        assert!(sudoku.has_unique_solution());
+        assert!(!sudoku.is_minimal());
+
+        let minimized = sudoku.minimize(1);
+        assert!(minimized.is_minimal());
+        assert!(
+            minimized
+                .to_value_string()
+                .chars()
+                .filter(|&ch| ch != '.')
+                .count()
+                < 40
+        );
+        let mut state = State::from_values(&minimized.to_value_string());
+        state.solve().unwrap();
+        assert_eq!(state.to_value_string(), solution);
+
+        let other = sudoku.minimize(2);
+        assert!(other.is_minimal());
+        assert_ne!(other.to_value_string(), minimized.to_value_string());
+    }
 }
@@ -1,14 +1,15 @@
-use crate::sudoku::{CellIndex, Sudoku};
+use crate::sudoku::{CellIndex, Sudoku, SudokuError};
 
 use std::cell::OnceCell;
-use std::iter::{Copied, FromIterator};
+use std::fmt::Debug;
+use std::iter::{Copied, FromIterator, Rev};
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Deref, DerefMut, Index, Sub, SubAssign};
 use std::usize;
 
 use arrayvec::ArrayVec;
 use itertools::Itertools;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct CellSet {
     bitset: u128,
     cells: OnceCell<ArrayVec<CellIndex, 81>>,
@@ -29,6 +30,13 @@ impl CellSet {
         }
     }
 
+    pub const fn from_bitset_const(bitset: u128) -> Self {
+        CellSet {
+            bitset,
+            cells: OnceCell::new(),
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.bitset == 0
     }
@@ -101,9 +109,40 @@ impl CellSet {
         self.values().iter().copied()
     }
 
+    /// The cells in the reverse order of `iter`.
+    pub fn rev_iter(&self) -> Rev<Copied<std::slice::Iter<'_, CellIndex>>> {
+        self.iter().rev()
+    }
+
+    /// The cells of the set that lie in `house`, in the order of the cells of `house`.
+    pub fn iter_in_house<'a>(
+        &'a self,
+        house: &'a NamedCellSet,
+    ) -> impl Iterator<Item = CellIndex> + 'a {
+        house.iter().filter(move |&cell| self.has(cell))
+    }
+
     pub fn to_string(&self, sudoku: &Sudoku) -> String {
         self.iter().map(|cell| sudoku.get_cell_name(cell)).join(",")
     }
+
+    /// Formats the cells as a comma separated list of cell names, e.g. `r1c1,r1c2`.
+    pub fn to_name_string(&self, sudoku: &Sudoku) -> String {
+        self.to_string(sudoku)
+    }
+
+    /// Parses the output of `to_name_string`.
+    pub fn from_name_string(s: &str, sudoku: &Sudoku) -> Result<Self, SudokuError> {
+        let mut set = Self::new();
+        for name in s
+            .split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+        {
+            set.add(sudoku.parse_cell_name(name)?);
+        }
+        Ok(set)
+    }
 }
 
 impl Index<usize> for &CellSet {
@@ -183,6 +222,19 @@ impl PartialEq for CellSet {
 
 impl Eq for CellSet {}
 
+impl Debug for CellSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}]",
+            self.iter()
+                .sorted()
+                .map(|cell| format!("r{}c{}", cell / 9 + 1, cell % 9 + 1))
+                .join(",")
+        )
+    }
+}
+
 impl<'a> IntoIterator for &'a CellSet {
     type Item = CellIndex;
     type IntoIter = Copied<std::slice::Iter<'a, CellIndex>>;
@@ -326,4 +378,51 @@ mod tests {
         assert_eq!(intersection.size(), 1);
         assert!(intersection.has(0));
     }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(format!("{:?}", CellSet::new()), "[]");
+        assert_eq!(
+            format!("{:?}", CellSet::from_iter([80, 0, 1])),
+            "[r1c1,r1c2,r9c9]"
+        );
+    }
+
+    #[test]
+    fn test_name_string() {
+        let sudoku = Sudoku::empty();
+        let set = CellSet::from_iter([0, 1, 40, 80]);
+        let names = set.to_name_string(&sudoku);
+        assert_eq!(names, "r1c1,r1c2,r5c5,r9c9");
+        assert_eq!(CellSet::from_name_string(&names, &sudoku).unwrap(), set);
+        assert!(CellSet::from_name_string("", &sudoku).unwrap().is_empty());
+        assert_eq!(
+            CellSet::from_name_string("r1c1,r0c1", &sudoku).unwrap_err(),
+            SudokuError::InvalidCellName("r0c1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rev_iter() {
+        assert_eq!(CellSet::new().rev_iter().count(), 0);
+        let set = CellSet::from_bitset(1 << 80 | 1 << 40 | 1 << 3);
+        assert_eq!(set.rev_iter().collect_vec(), vec![80, 40, 3]);
+        assert_eq!(
+            set.rev_iter().collect_vec(),
+            set.iter().collect_vec().into_iter().rev().collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_iter_in_house() {
+        let mut row = NamedCellSet::new("r1".to_string(), 9);
+        *row = CellSet::from_iter(0..9);
+        assert_eq!(CellSet::new().iter_in_house(&row).count(), 0);
+
+        let full = CellSet::from_iter(0..81);
+        assert_eq!(full.iter_in_house(&row).collect_vec(), row.values());
+
+        let set = CellSet::from_iter([80, 7, 9, 2]);
+        assert_eq!(set.iter_in_house(&row).collect_vec(), vec![2, 7]);
+    }
 }
@@ -1,9 +1,11 @@
 mod cellset;
 mod combination_generator;
 mod combination_generator2;
+mod rng;
 mod valueset;
 
 pub use cellset::{CellSet, NamedCellSet};
 pub use combination_generator::{combinations, CombinationOptions};
 pub use combination_generator2::{combinations as comb, combinations_ref as comb_ref};
+pub use rng::Rng;
 pub use valueset::ValueSet;
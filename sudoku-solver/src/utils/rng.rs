@@ -0,0 +1,49 @@
+/// A small deterministic pseudo random generator (SplitMix64), so that puzzles built from a seed
+/// are reproducible without depending on a random crate.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle() {
+        let mut items = (0..81).collect::<Vec<_>>();
+        Rng::new(42).shuffle(&mut items);
+        let mut other = (0..81).collect::<Vec<_>>();
+        Rng::new(42).shuffle(&mut other);
+        assert_eq!(items, other);
+        assert_ne!(items, (0..81).collect::<Vec<_>>());
+
+        items.sort();
+        assert_eq!(items, (0..81).collect::<Vec<_>>());
+    }
+}
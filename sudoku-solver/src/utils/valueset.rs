@@ -1,14 +1,16 @@
 use crate::sudoku::CellValue;
 
 use std::cell::OnceCell;
+use std::fmt::Debug;
 use std::iter::{Copied, FromIterator};
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Index, Sub, SubAssign};
 use std::usize;
 
 use arrayvec::ArrayVec;
 use bitset_core::BitSet;
+use itertools::Itertools;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ValueSet {
     bitset: u16,
     values: OnceCell<ArrayVec<CellValue, 9>>,
@@ -172,6 +174,12 @@ impl PartialEq for ValueSet {
 
 impl Eq for ValueSet {}
 
+impl Debug for ValueSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{{}}}", self.iter().sorted().join(","))
+    }
+}
+
 impl<'a> IntoIterator for &'a ValueSet {
     type Item = CellValue;
     type IntoIter = Copied<std::slice::Iter<'a, CellValue>>;
@@ -188,3 +196,14 @@ impl Index<usize> for &ValueSet {
         &self.values()[index]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(format!("{:?}", ValueSet::new()), "{}");
+        assert_eq!(format!("{:?}", ValueSet::from_iter([5, 1, 3])), "{1,3,5}");
+    }
+}
@@ -272,25 +272,58 @@ fn all_techniques_str() -> Vec<String> {
         "hidden_single".to_string(),
 
         "locked_candidates".to_string(),
+        "locked_candidates_pointing".to_string(),
+        "locked_candidates_claiming".to_string(),
 
         "hidden_subset".to_string(),
         "naked_subset".to_string(),
+        "distributed_disjoint_subset".to_string(),
 
         "two_string_kite".to_string(),
         "skyscraper".to_string(),
         "rectangle_elimination".to_string(),
 
+        "chute_remote_pair".to_string(),
+
         "w_wing".to_string(),
         "xy_wing".to_string(),
         "xyz_wing".to_string(),
+        "wxyz_wing".to_string(),
+        "als_xz".to_string(),
+        "death_blossom".to_string(),
 
         "basic_fish".to_string(),
+        "x_wing".to_string(),
+        "swordfish".to_string(),
+        "jellyfish".to_string(),
         "finned_fish".to_string(),
+        "sashimi_fish".to_string(),
         "franken_fish".to_string(),
         "mutant_fish".to_string(),
+        "kraken_fish".to_string(),
+
+        "simple_coloring".to_string(),
+        "three_dimensional_medusa".to_string(),
+        "medusa_3d".to_string(),
+
+        "unique_rectangle".to_string(),
+        "hidden_rectangle".to_string(),
+        "avoidable_rectangle".to_string(),
+        "bug".to_string(),
+
+        "exocet".to_string(),
 
+        "killer_combinations".to_string(),
+
+        "remote_pairs".to_string(),
+        "xy_chain".to_string(),
+        "grouped_x_chain".to_string(),
+        "aic_type2".to_string(),
+        "nice_loop".to_string(),
         "forced_chain".to_string(),
 
+        "template".to_string(),
+
         "guess".to_string(),
     ]
 }
@@ -299,7 +332,8 @@ fn default_techniques_str() -> Vec<String> {
     vec![
         "naked_single".to_string(),
         "hidden_single".to_string(),
-        "locked_candidates".to_string(),
+        "locked_candidates_pointing".to_string(),
+        "locked_candidates_claiming".to_string(),
         "hidden_subset".to_string(),
         "naked_subset".to_string(),
 